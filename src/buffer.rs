@@ -1,88 +1,351 @@
+use crate::command;
+use crate::process;
 use crate::util;
 use crate::Result;
 use std::io::BufRead;
+use std::io::Read;
 use std::io::Seek;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::OpenOptionsExt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum State {
+    Normal,
+    Truncated, // reset after file shrunk
+    Rotated,   // reopened after path got a new file
+    Missing,   // path removed, still showing old file
+    Exited,    // process of /proc/<pid> path exited, still showing old file
+    Waiting,   // path not yet created
+}
+
+// file read in place, or contents of pseudo file, command output or stream
+// read into memory
+trait Source: BufRead + Seek + std::fmt::Debug + Send {
+    fn len(&self) -> std::io::Result<u64>;
+}
+
+impl Source for std::io::BufReader<std::fs::File> {
+    fn len(&self) -> std::io::Result<u64> {
+        Ok(self.get_ref().metadata()?.len())
+    }
+}
+
+impl Source for std::io::Cursor<Vec<u8>> {
+    fn len(&self) -> std::io::Result<u64> {
+        u64::try_from(self.get_ref().len()).map_err(|_| util::error())
+    }
+}
 
 #[derive(Debug)]
 pub(crate) struct Buffer {
+    path: String,
+    state: State,
     chunk: Vec<String>,
-    reader: Option<std::io::BufReader<std::fs::File>>,
+    reader: Option<Box<dyn Source>>,
+    inode: (u64, u64),
+    pseudo: bool, // procfs, sysfs, etc, command or stream
+    status: Option<command::Status>,
+    timeout: std::time::Duration,
+    stream: Option<(std::fs::File, Vec<u8>)>, // stdin or FIFO, and lines read
+    ring: usize,                              // max lines kept for stream
+    index: Vec<u64>,                          // offset of each line following a newline
+    scanned: u64,                             // offset up to which index is valid
     curline: usize,
     maxline: usize,
+    tabstop: usize,
+    generation: (u64, std::time::Instant), // count and time of updates
+    epoch: u64, // count of updates which may have changed lines read before
+    fetched: Option<std::io::Result<Fetched>>, // for the next update
 }
 
+// contents of pseudo file or command output, and exit status if command
+pub(crate) type Fetched = (Vec<u8>, Option<command::Status>);
+
 impl Buffer {
     pub(crate) fn new() -> Result<Self> {
         let mut buffer = Self {
+            path: String::new(),
+            state: State::Normal,
             chunk: Vec::new(),
             reader: None,
+            inode: (0, 0),
+            pseudo: false,
+            status: None,
+            timeout: std::time::Duration::from_secs(1),
+            stream: None,
+            ring: 10000,
+            index: Vec::new(),
+            scanned: 0,
             curline: 0,
             maxline: 0,
+            tabstop: 8,
+            generation: (0, std::time::Instant::now()),
+            epoch: 0,
+            fetched: None,
         };
         assert!(buffer.is_dead());
         buffer.update()?;
         Ok(buffer)
     }
 
+    // Open path without reading it, which is left to the first check() or
+    // update(), as it may take long, e.g. command or large file.
     pub(crate) fn init(&mut self, f: &str) -> std::io::Result<()> {
-        assert!(self.reader.is_none());
-        let fp = std::fs::File::open(f)?;
-        self.reader = Some(std::io::BufReader::new(fp));
-        self.update()?;
+        assert!(self.is_dead());
+        self.path = f.to_string();
+        match self.open() {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.state = State::Waiting;
+                Ok(())
+            }
+            Err(e) => {
+                self.path.clear();
+                Err(e)
+            }
+        }
+    }
+
+    fn open(&mut self) -> std::io::Result<()> {
+        if util::get_command(&self.path).is_some() {
+            self.pseudo = true;
+            self.reader = Some(Box::new(std::io::Cursor::new(vec![])));
+        } else if util::is_stdin(&self.path) || util::is_fifo(&self.path) {
+            let f = if util::is_stdin(&self.path) {
+                util::get_stdin_path()?
+            } else {
+                self.path.clone()
+            };
+            // nonblocking, or FIFO blocks until opened by writer
+            let fp = std::fs::OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(f)?;
+            self.pseudo = true;
+            self.stream = Some((fp, vec![]));
+            self.reader = Some(Box::new(std::io::Cursor::new(vec![])));
+        } else {
+            let fp = std::fs::File::open(&self.path)?;
+            let m = fp.metadata()?;
+            self.inode = (m.dev(), m.ino());
+            self.pseudo = util::is_pseudo_file(&self.path);
+            self.reader = Some(Box::new(std::io::BufReader::new(fp)));
+        }
+        self.reset();
         Ok(())
     }
 
+    fn reset(&mut self) {
+        self.epoch += 1;
+        self.index.clear();
+        self.scanned = 0;
+        self.chunk.clear();
+        self.curline = 0;
+        self.maxline = 0;
+    }
+
+    pub(crate) fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    pub(crate) fn set_tabstop(&mut self, n: usize) {
+        self.tabstop = n;
+    }
+
+    pub(crate) fn set_timeout(&mut self, d: std::time::Duration) {
+        self.timeout = d;
+    }
+
+    pub(crate) fn set_ring(&mut self, n: usize) {
+        self.ring = n;
+    }
+
+    // exit status of the last run if command
+    pub(crate) fn get_status(&self) -> Option<command::Status> {
+        self.status
+    }
+
+    // changes whenever contents are read again
+    pub(crate) fn get_generation(&self) -> (u64, std::time::Instant) {
+        self.generation
+    }
+
+    // Changes unless lines were only appended since, e.g. truncated file or
+    // pseudo file read anew.
+    pub(crate) fn get_epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub(crate) fn get_state(&self) -> State {
+        self.state
+    }
+
     pub(crate) fn get_max_line(&mut self) -> usize {
         self.maxline
     }
 
-    pub(crate) fn is_dead(&mut self) -> bool {
-        self.reader.is_none()
+    pub(crate) fn is_dead(&self) -> bool {
+        self.path.is_empty()
     }
 
-    pub(crate) fn update(&mut self) -> std::io::Result<()> {
+    pub(crate) fn is_ready(&mut self) -> bool {
+        self.reader.is_some()
+    }
+
+    pub(crate) fn is_pseudo(&self) -> bool {
+        self.pseudo
+    }
+
+    // Returns timeout for fetch() if update reads contents anew as a whole,
+    // i.e. pseudo file or command, but not stream.
+    pub(crate) fn get_fetch_timeout(&self) -> Option<std::time::Duration> {
+        (self.pseudo && self.stream.is_none() && self.reader.is_some()).then_some(self.timeout)
+    }
+
+    // Use result of fetch() done by caller for the next update, instead of
+    // reading again.
+    pub(crate) fn set_fetched(&mut self, x: std::io::Result<Fetched>) {
+        self.fetched = Some(x);
+    }
+
+    // Reopen path if it now refers to a different file, otherwise update.
+    // Returns true if reopened.
+    pub(crate) fn check(&mut self) -> std::io::Result<bool> {
         if self.is_dead() {
+            return Ok(false);
+        }
+        if util::get_command(&self.path).is_some() || self.stream.is_some() {
+            self.update()?;
+            return Ok(false);
+        }
+        let m = match std::fs::metadata(&self.path) {
+            Ok(v) => v,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.state = if !self.is_ready() {
+                    State::Waiting
+                } else if process::get_pid(&self.path).is_some() {
+                    State::Exited
+                } else {
+                    State::Missing
+                };
+                self.fetched = None;
+                return Ok(false);
+            }
+            Err(e) if e.raw_os_error() == Some(libc::ESRCH) => {
+                // procfs may report exiting process this way
+                self.state = if self.is_ready() {
+                    State::Exited
+                } else {
+                    State::Waiting
+                };
+                self.fetched = None;
+                return Ok(false);
+            }
+            Err(e) => return Err(e),
+        };
+        if !self.is_ready() || (m.dev(), m.ino()) != self.inode {
+            log::info!("{}: reopen {}", util::function!(), self.path);
+            self.state = if self.is_ready() {
+                State::Rotated
+            } else {
+                State::Normal
+            };
+            self.open()?;
+            self.update()?;
+            return Ok(true);
+        }
+        self.state = State::Normal;
+        self.update()?;
+        Ok(false)
+    }
+
+    // Extend line index from the last scanned offset, unless truncated.
+    // Pseudo file is read again as a whole, as it reports no size and some
+    // only renew contents on open. Command is run again likewise, and stream
+    // is read for lines written since.
+    pub(crate) fn update(&mut self) -> std::io::Result<()> {
+        if !self.is_ready() {
             return Ok(());
         }
+        if self.pseudo {
+            let b = if let Some((fp, b)) = self.stream.as_mut() {
+                if read_stream(fp, b, self.ring)? {
+                    self.chunk.clear(); // lines shifted
+                }
+                b.clone()
+            } else {
+                let (b, x) = match self.fetched.take() {
+                    Some(v) => v?,
+                    None => fetch(&self.path, self.timeout)?,
+                };
+                if x.is_some() {
+                    self.status = x;
+                }
+                b
+            };
+            self.reader = Some(Box::new(std::io::Cursor::new(b)));
+            self.index.clear();
+            self.scanned = 0;
+            self.epoch += 1;
+        }
+        let r = self.reader.as_mut().ok_or_else(util::error)?;
+        if r.len()? < self.scanned {
+            log::info!("{}: truncated at {}", util::function!(), self.scanned);
+            self.reset();
+            self.state = State::Truncated;
+        }
         let r = self.reader.as_mut().ok_or_else(util::error)?;
         let tmp = r.stream_position()?;
-        r.seek(std::io::SeekFrom::Start(0))?; // affects BufRead::lines
-        self.maxline = 0;
-        for _ in r./*by_ref().*/lines() {
-            self.maxline += 1;
+        r.seek(std::io::SeekFrom::Start(self.scanned))?;
+        let mut pos = self.scanned;
+        loop {
+            let buf = r.fill_buf()?;
+            if buf.is_empty() {
+                break;
+            }
+            for (i, _) in buf.iter().enumerate().filter(|(_, &c)| c == b'\n') {
+                self.index
+                    .push(pos + u64::try_from(i).map_err(|_| util::error())? + 1);
+            }
+            let n = buf.len();
+            pos += u64::try_from(n).map_err(|_| util::error())?;
+            r.consume(n);
+        }
+        self.scanned = pos;
+        self.maxline = self.index.len();
+        if self.index.last().copied().unwrap_or(0) < self.scanned {
+            self.maxline += 1; // last line without newline
         }
         r.seek(std::io::SeekFrom::Start(tmp))?;
+        self.generation = (self.generation.0 + 1, std::time::Instant::now());
         Ok(())
     }
 
-    pub(crate) fn readline(
-        &mut self,
-        showlnum: bool,
-        blinkline: bool,
-    ) -> std::io::Result<(usize, String, bool)> {
-        let mut s = String::new();
+    pub(crate) fn readline(&mut self, blinkline: bool) -> std::io::Result<(usize, String, bool)> {
+        let mut b = vec![];
         if self
             .reader
             .as_mut()
             .ok_or_else(util::error)?
-            .read_line(&mut s)?
+            .read_until(b'\n', &mut b)?
             == 0
-            || s.is_empty()
         {
             return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput));
         }
 
-        // rstrip \n and then replace % with %%
-        if let Some(v) = s.strip_suffix('\n') {
-            s = v.replace('%', "%%");
-        } else {
-            s = s.replace('%', "%%");
+        // rstrip \n or \r\n
+        if b.last() == Some(&b'\n') {
+            b.pop();
+            if b.last() == Some(&b'\r') {
+                b.pop();
+            }
         }
+        let s = util::sanitize_line(&b, self.tabstop);
 
         let standout;
         if blinkline {
             if self.curline >= self.chunk.len() {
-                self.chunk.resize(self.chunk.len() * 2 + 1, String::new());
+                self.chunk.resize(self.curline * 2 + 1, String::new());
             }
             standout =
                 !self.chunk[self.curline].is_empty() && self.chunk[self.curline] != s.as_str();
@@ -93,19 +356,312 @@ impl Buffer {
 
         let pos = self.curline;
         self.curline += 1;
-        if showlnum {
-            s = format!("{} {}", self.curline, s);
-        }
         Ok((pos, s, standout))
     }
 
+    // Find line matching f(pos, line) next to line n, wrapping around.
+    pub(crate) fn find<F: Fn(usize, &str) -> bool>(
+        &mut self,
+        n: usize,
+        forward: bool,
+        f: F,
+    ) -> std::io::Result<Option<usize>> {
+        if forward {
+            self.seek_line(n + 1)?;
+            while let Ok((pos, s, _)) = self.readline(false) {
+                if f(pos, &s) {
+                    return Ok(Some(pos));
+                }
+            }
+            self.seek_line(0)?;
+            while let Ok((pos, s, _)) = self.readline(false) {
+                if pos > n {
+                    break;
+                }
+                if f(pos, &s) {
+                    return Ok(Some(pos));
+                }
+            }
+            Ok(None)
+        } else {
+            let mut prev = None;
+            let mut last = None;
+            self.seek_line(0)?;
+            while let Ok((pos, s, _)) = self.readline(false) {
+                if pos >= n && prev.is_some() {
+                    break;
+                }
+                if f(pos, &s) {
+                    if pos < n {
+                        prev = Some(pos);
+                    } else {
+                        last = Some(pos);
+                    }
+                }
+            }
+            Ok(prev.or(last))
+        }
+    }
+
     // caller needs to test if ready
-    pub(crate) fn clear(&mut self) -> std::io::Result<()> {
+    pub(crate) fn seek_line(&mut self, n: usize) -> std::io::Result<()> {
+        let off = if n == 0 {
+            0
+        } else if let Some(&v) = self.index.get(n - 1) {
+            v
+        } else {
+            self.scanned
+        };
         self.reader
             .as_mut()
             .ok_or_else(util::error)?
-            .seek(std::io::SeekFrom::Start(0))?;
-        self.curline = 0;
+            .seek(std::io::SeekFrom::Start(off))?;
+        self.curline = n;
         Ok(())
     }
 }
+
+// Read pseudo file f, or run command if f is a command path. This may take
+// up to timeout, so that window threads call it without locking the window.
+pub(crate) fn fetch(f: &str, timeout: std::time::Duration) -> std::io::Result<Fetched> {
+    if let Some(s) = util::get_command(f) {
+        let (b, x) = command::run(s, timeout)?;
+        Ok((b, Some(x)))
+    } else {
+        Ok((std::fs::read(f)?, None))
+    }
+}
+
+// Append whatever readable from fp to b, and drop the oldest lines beyond
+// n lines. Returns true if dropped.
+fn read_stream(fp: &mut std::fs::File, b: &mut Vec<u8>, n: usize) -> std::io::Result<bool> {
+    let mut buf = [0; 4096];
+    loop {
+        match fp.read(&mut buf) {
+            Ok(0) => break, // no writer
+            Ok(x) => b.extend_from_slice(&buf[..x]),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+    // last line without newline counts
+    let l = b.iter().filter(|&&c| c == b'\n').count() + usize::from(b.last() != Some(&b'\n'));
+    if l <= n {
+        return Ok(false);
+    }
+    let pos = if n == 0 {
+        b.len()
+    } else {
+        b.iter()
+            .enumerate()
+            .filter(|(_, &c)| c == b'\n')
+            .nth(l - n - 1)
+            .map_or(b.len(), |(i, _)| i + 1)
+    };
+    b.drain(..pos);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    fn readlines(b: &mut super::Buffer, n: usize) -> Vec<String> {
+        b.seek_line(n).unwrap();
+        let mut v = vec![];
+        while let Ok((_, s, _)) = b.readline(false) {
+            v.push(s);
+        }
+        v
+    }
+
+    #[test]
+    fn test_update() {
+        let f = std::env::temp_dir().join(format!("procstat-rs-test-{}", std::process::id()));
+        let f = f.to_str().unwrap();
+        std::fs::write(f, "a\nb\n").unwrap();
+
+        let mut b = super::Buffer::new().unwrap();
+        b.init(f).unwrap();
+        assert_eq!(b.get_max_line(), 0); // read on update
+        b.update().unwrap();
+        assert_eq!(b.get_max_line(), 2);
+        assert_eq!(readlines(&mut b, 1), ["b"]);
+        let epoch = b.get_epoch();
+
+        let mut fp = std::fs::OpenOptions::new().append(true).open(f).unwrap();
+        fp.write_all(b"c").unwrap();
+        b.update().unwrap();
+        assert_eq!(b.get_max_line(), 3);
+        assert_eq!(readlines(&mut b, 2), ["c"]);
+
+        fp.write_all(b"c\nd\n").unwrap();
+        b.update().unwrap();
+        assert_eq!(b.get_max_line(), 4);
+        assert_eq!(readlines(&mut b, 2), ["cc", "d"]);
+        assert_eq!(readlines(&mut b, 4), Vec::<String>::new());
+        assert_eq!(b.get_epoch(), epoch);
+
+        std::fs::write(f, "x\n").unwrap();
+        b.update().unwrap();
+        assert_eq!(b.get_max_line(), 1);
+        assert_ne!(b.get_epoch(), epoch);
+        assert_eq!(readlines(&mut b, 0), ["x"]);
+
+        std::fs::remove_file(f).unwrap();
+    }
+
+    #[test]
+    fn test_check() {
+        let f = std::env::temp_dir().join(format!("procstat-rs-test-check-{}", std::process::id()));
+        let f = f.to_str().unwrap();
+        let g = format!("{f}.1");
+
+        let mut b = super::Buffer::new().unwrap();
+        b.init(f).unwrap();
+        b.update().unwrap();
+        assert_eq!(b.get_state(), super::State::Waiting);
+        assert!(!b.is_ready());
+
+        std::fs::write(f, "a\n").unwrap();
+        assert!(b.check().unwrap());
+        assert_eq!(b.get_state(), super::State::Normal);
+        assert_eq!(readlines(&mut b, 0), ["a"]);
+
+        std::fs::rename(f, &g).unwrap();
+        assert!(!b.check().unwrap());
+        assert_eq!(b.get_state(), super::State::Missing);
+        assert_eq!(readlines(&mut b, 0), ["a"]);
+
+        std::fs::write(f, "b\nc\n").unwrap();
+        assert!(b.check().unwrap());
+        assert_eq!(b.get_state(), super::State::Rotated);
+        assert_eq!(readlines(&mut b, 0), ["b", "c"]);
+
+        std::fs::write(f, "d\n").unwrap();
+        assert!(!b.check().unwrap());
+        assert_eq!(b.get_state(), super::State::Truncated);
+        assert_eq!(readlines(&mut b, 0), ["d"]);
+
+        std::fs::remove_file(f).unwrap();
+        std::fs::remove_file(&g).unwrap();
+    }
+
+    #[test]
+    fn test_update_pseudo() {
+        let mut b = super::Buffer::new().unwrap();
+        b.init("/proc/self/stat").unwrap();
+        b.update().unwrap();
+        assert!(b.is_pseudo());
+        assert_eq!(b.get_max_line(), 1);
+        b.update().unwrap();
+        assert_eq!(b.get_max_line(), 1);
+        assert_eq!(readlines(&mut b, 0).len(), 1);
+    }
+
+    #[test]
+    fn test_check_exited() {
+        let mut c = std::process::Command::new("sleep")
+            .arg("10")
+            .spawn()
+            .unwrap();
+        let mut b = super::Buffer::new().unwrap();
+        b.init(&format!("/proc/{}/status", c.id())).unwrap();
+        b.update().unwrap();
+        assert!(!b.check().unwrap());
+        assert_eq!(b.get_state(), super::State::Normal);
+
+        c.kill().unwrap();
+        c.wait().unwrap();
+        assert!(!b.check().unwrap());
+        assert_eq!(b.get_state(), super::State::Exited);
+        assert!(!readlines(&mut b, 0).is_empty());
+    }
+
+    #[test]
+    fn test_update_command() {
+        let f = std::env::temp_dir().join(format!("procstat-rs-test-cmd-{}", std::process::id()));
+        let f = f.to_str().unwrap();
+        std::fs::write(f, "a\n").unwrap();
+
+        let mut b = super::Buffer::new().unwrap();
+        b.init(&format!("!cat {f}; exit 1")).unwrap();
+        b.update().unwrap();
+        assert!(b.is_pseudo());
+        assert_eq!(b.get_status(), Some(crate::command::Status::Exit(1)));
+        assert_eq!(readlines(&mut b, 0), ["a"]);
+
+        std::fs::write(f, "b\nc\n").unwrap();
+        assert!(!b.check().unwrap());
+        assert_eq!(b.get_state(), super::State::Normal);
+        assert_eq!(readlines(&mut b, 0), ["b", "c"]);
+
+        // fetched without buffer, as done by window thread
+        let t = b.get_fetch_timeout().unwrap();
+        let x = super::fetch(b.get_path(), t).unwrap();
+        assert_eq!(
+            x,
+            (b"b\nc\n".to_vec(), Some(crate::command::Status::Exit(1)))
+        );
+        b.set_fetched(Ok((b"d\n".to_vec(), Some(crate::command::Status::Exit(0)))));
+        assert!(!b.check().unwrap());
+        assert_eq!(b.get_status(), Some(crate::command::Status::Exit(0)));
+        assert_eq!(readlines(&mut b, 0), ["d"]);
+
+        std::fs::remove_file(f).unwrap();
+    }
+
+    #[test]
+    fn test_update_fifo() {
+        let f = std::env::temp_dir().join(format!("procstat-rs-test-fifo-{}", std::process::id()));
+        let f = f.to_str().unwrap();
+        let p = std::ffi::CString::new(f).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(p.as_ptr(), 0o600) }, 0);
+
+        let mut b = super::Buffer::new().unwrap();
+        b.set_ring(2);
+        b.init(f).unwrap();
+        b.update().unwrap();
+        assert!(b.is_pseudo());
+        assert!(b.get_fetch_timeout().is_none());
+        assert_eq!(b.get_max_line(), 0);
+
+        let mut fp = std::fs::OpenOptions::new().write(true).open(f).unwrap();
+        fp.write_all(b"a\nb\n").unwrap();
+        b.update().unwrap();
+        assert_eq!(readlines(&mut b, 0), ["a", "b"]);
+
+        fp.write_all(b"c\nd").unwrap();
+        b.update().unwrap();
+        assert_eq!(readlines(&mut b, 0), ["c", "d"]);
+        fp.write_all(b"d\n").unwrap();
+        drop(fp);
+        assert!(!b.check().unwrap());
+        assert_eq!(readlines(&mut b, 0), ["c", "dd"]);
+
+        std::fs::remove_file(f).unwrap();
+    }
+
+    #[test]
+    fn test_find() {
+        let f = std::env::temp_dir().join(format!("procstat-rs-test-find-{}", std::process::id()));
+        let f = f.to_str().unwrap();
+        std::fs::write(f, "foo\nbar\nbaz\nfoo\n").unwrap();
+
+        let mut b = super::Buffer::new().unwrap();
+        b.init(f).unwrap();
+        b.update().unwrap();
+        let re = regex::Regex::new("^ba").unwrap();
+        assert_eq!(b.find(0, true, |_, s| re.is_match(s)).unwrap(), Some(1));
+        assert_eq!(b.find(1, true, |_, s| re.is_match(s)).unwrap(), Some(2));
+        assert_eq!(b.find(2, true, |_, s| re.is_match(s)).unwrap(), Some(1));
+        assert_eq!(b.find(2, false, |_, s| re.is_match(s)).unwrap(), Some(1));
+        assert_eq!(b.find(1, false, |_, s| re.is_match(s)).unwrap(), Some(2));
+        let re = regex::Regex::new("qux").unwrap();
+        assert_eq!(b.find(0, true, |_, s| re.is_match(s)).unwrap(), None);
+        assert_eq!(b.find(0, false, |_, s| re.is_match(s)).unwrap(), None);
+
+        std::fs::remove_file(f).unwrap();
+    }
+}
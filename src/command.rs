@@ -0,0 +1,120 @@
+use crate::util;
+use std::io::Read;
+use std::os::fd::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::os::unix::process::ExitStatusExt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Status {
+    Exit(i32),
+    Signal(i32),
+    Timeout, // killed after timeout
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Exit(n) => write!(f, "exit {n}"),
+            Status::Signal(n) => write!(f, "signal {n}"),
+            Status::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
+// Run command line s by shell and return its stdout and stderr in the order
+// written. Command and its children are killed if not done within timeout.
+pub(crate) fn run(s: &str, timeout: std::time::Duration) -> std::io::Result<(Vec<u8>, Status)> {
+    let (mut r, w) = std::io::pipe()?;
+    // own process group, so that terminal signals don't reach it and kill
+    // takes its children along
+    let mut child = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(s)
+        .stdin(std::process::Stdio::null())
+        .stdout(w.try_clone()?)
+        .stderr(w)
+        .process_group(0)
+        .spawn()?;
+
+    let deadline = std::time::Instant::now() + timeout;
+    let mut b = vec![];
+    let mut buf = [0; 4096];
+    let done = loop {
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            break false;
+        }
+        if !util::poll_read(&[r.as_raw_fd()], Some(deadline - now))?[0] {
+            continue;
+        }
+        match r.read(&mut buf)? {
+            0 => break true,
+            n => b.extend_from_slice(&buf[..n]),
+        }
+    };
+    let x = if done {
+        wait(&mut child, deadline)?
+    } else {
+        None
+    };
+    let Some(x) = x else {
+        log::info!("{}: timeout {}", util::function!(), s);
+        if let Ok(pid) = i32::try_from(child.id()) {
+            unsafe { libc::kill(-pid, libc::SIGKILL) };
+        }
+        child.wait()?;
+        return Ok((b, Status::Timeout));
+    };
+    let status = match (x.code(), x.signal()) {
+        (Some(n), _) => Status::Exit(n),
+        (None, Some(n)) => Status::Signal(n),
+        (None, None) => return Err(util::error()),
+    };
+    Ok((b, status))
+}
+
+// Wait for child until deadline, as it may keep running after closing its
+// output. Returns None if not exited by then.
+fn wait(
+    child: &mut std::process::Child,
+    deadline: std::time::Instant,
+) -> std::io::Result<Option<std::process::ExitStatus>> {
+    loop {
+        if let Some(x) = child.try_wait()? {
+            return Ok(Some(x));
+        }
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep((deadline - now).min(std::time::Duration::from_millis(10)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_run() {
+        let t = std::time::Duration::from_secs(5);
+        let (b, x) = super::run("echo a; echo b >&2; echo c", t).unwrap();
+        assert_eq!(b, b"a\nb\nc\n");
+        assert_eq!(x, super::Status::Exit(0));
+        assert_eq!(x.to_string(), "exit 0");
+
+        let (b, x) = super::run("exit 3", t).unwrap();
+        assert!(b.is_empty());
+        assert_eq!(x, super::Status::Exit(3));
+
+        let (_, x) = super::run("kill -9 $$", t).unwrap();
+        assert_eq!(x, super::Status::Signal(9));
+
+        let t = std::time::Duration::from_millis(100);
+        let (b, x) = super::run("echo a; sleep 10", t).unwrap();
+        assert_eq!(b, b"a\n");
+        assert_eq!(x, super::Status::Timeout);
+
+        let (b, x) = super::run("echo a; exec >&- 2>&-; sleep 10", t).unwrap();
+        assert_eq!(b, b"a\n");
+        assert_eq!(x, super::Status::Timeout);
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(feature = "curses")]
+use crate::curses as screen;
+
+#[cfg(feature = "stdout")]
+use crate::stdout as screen;
+
+use crate::keymap;
+use crate::util;
+
+// per path settings in [path."<path>"] table
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct PathConfig {
+    options: Option<String>, // same as ":<options>" of path argument
+    include: Option<String>,
+    exclude: Option<String>,
+}
+
+// Each field corresponds to a command line option, which takes precedence
+// over the config file.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    layout: Option<String>,
+    interval: Option<String>,
+    fg: Option<String>,
+    bg: Option<String>,
+    lnum: Option<bool>,
+    fold: Option<bool>,
+    rotate: Option<bool>,
+    blink: Option<bool>,
+    follow: Option<bool>,
+    table: Option<bool>,
+    delta: Option<String>,
+    tabstop: Option<usize>,
+    ring: Option<usize>,
+    usedelay: Option<bool>,
+    debug: Option<bool>,
+    layouts: std::collections::HashMap<String, String>, // name to layout
+    keys: std::collections::HashMap<String, String>,    // key to action
+    path: std::collections::HashMap<String, PathConfig>,
+}
+
+impl Config {
+    pub(crate) fn get_layout(&self) -> Option<&str> {
+        self.layout.as_deref()
+    }
+
+    // named layout if defined, otherwise s as is
+    pub(crate) fn resolve_layout<'a>(&'a self, s: &'a str) -> &'a str {
+        match self.layouts.get(s) {
+            Some(v) => v,
+            None => s,
+        }
+    }
+
+    pub(crate) fn apply(&self, opt: &mut crate::Opt) -> crate::Result<()> {
+        if let Some(v) = &self.interval {
+            let x = crate::parse_interval(v)?;
+            opt.sinterval = x / 1000;
+            opt.minterval = x % 1000;
+        }
+        if let Some(v) = &self.fg {
+            opt.fgcolor = parse_color(v)?;
+        }
+        if let Some(v) = &self.bg {
+            opt.bgcolor = parse_color(v)?;
+        }
+        if let Some(v) = self.lnum {
+            opt.showlnum = v;
+        }
+        if let Some(v) = self.fold {
+            opt.foldline = v;
+        }
+        if let Some(v) = self.rotate {
+            opt.rotatecol = v;
+        }
+        if let Some(v) = self.blink {
+            opt.blinkline = v;
+        }
+        if let Some(v) = self.follow {
+            opt.follow = v;
+        }
+        if let Some(v) = self.table {
+            opt.table = v;
+        }
+        if let Some(v) = &self.delta {
+            opt.delta = match v.as_str() {
+                "raw" => crate::delta::Mode::Raw,
+                "delta" => crate::delta::Mode::Delta,
+                "rate" => crate::delta::Mode::Rate,
+                _ => return Err(crate::invalid_input(&format!("invalid delta {v}"))),
+            };
+        }
+        if let Some(v) = self.tabstop {
+            opt.tabstop = v;
+        }
+        if let Some(v) = self.ring {
+            opt.ring = v;
+        }
+        if let Some(v) = self.usedelay {
+            opt.usedelay = v;
+        }
+        if let Some(v) = self.debug {
+            opt.debug = v;
+        }
+        for (k, v) in &self.keys {
+            let Some(x) = keymap::string_to_key(k) else {
+                return Err(crate::invalid_input(&format!("invalid key {k}")));
+            };
+            let Some(a) = keymap::string_to_action(v) else {
+                return Err(crate::invalid_input(&format!("invalid action {v}")));
+            };
+            opt.keymap.push((x, a));
+        }
+        for (k, v) in &self.path {
+            let f = util::get_abspath(k)?;
+            if let Some(s) = &v.options {
+                // validate here so that error refers to config file
+                let mut wopt = opt.get_window_opt(&f);
+                if let Err(e) = crate::parse_window_opt(s, &mut wopt) {
+                    return Err(crate::invalid_input(&format!("{k}: {e}")));
+                }
+                opt.pathopt.push((f.clone(), s.clone()));
+            }
+            if let Some(s) = &v.include {
+                opt.include.push((f.clone(), regex::Regex::new(s)?));
+            }
+            if let Some(s) = &v.exclude {
+                opt.exclude.push((f.clone(), regex::Regex::new(s)?));
+            }
+        }
+        Ok(())
+    }
+}
+
+const COLOR_LIST: [&str; 8] = [
+    "black", "blue", "cyan", "green", "magenta", "red", "white", "yellow",
+];
+
+fn parse_color(s: &str) -> crate::Result<i16> {
+    if COLOR_LIST.contains(&s) {
+        Ok(screen::string_to_color(s))
+    } else {
+        Err(crate::invalid_input(&format!("invalid color {s}")))
+    }
+}
+
+// default config file path, i.e. ".<progname>.toml" under home directory
+pub(crate) fn get_path(home: &str, progname: &str) -> crate::Result<String> {
+    let name = format!(".{}.toml", util::get_basename(progname)?);
+    Ok(util::join_path(home, &name))
+}
+
+// Missing file is not an error unless explicitly specified.
+pub(crate) fn load(f: &str, required: bool) -> crate::Result<Config> {
+    let s = match std::fs::read_to_string(f) {
+        Ok(v) => v,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => {
+            return Ok(Config::default())
+        }
+        Err(e) => return Err(Box::new(e)),
+    };
+    Ok(toml::from_str(&s)?)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_apply() {
+        let s = r#"
+            layout = "mem"
+            interval = "500ms"
+            lnum = true
+            delta = "rate"
+            [layouts]
+            mem = "12"
+            [keys]
+            "C-n" = "next-window"
+            [path."/does/not/exist"]
+            options = "interval=2s,nolnum"
+            include = "^Mem"
+        "#;
+        let cfg: super::Config = toml::from_str(s).unwrap();
+        assert_eq!(cfg.resolve_layout(cfg.get_layout().unwrap()), "12");
+        assert_eq!(cfg.resolve_layout("123"), "123");
+
+        let mut opt = crate::Opt {
+            ..Default::default()
+        };
+        cfg.apply(&mut opt).unwrap();
+        assert_eq!(opt.sinterval, 0);
+        assert_eq!(opt.minterval, 500);
+        assert!(opt.showlnum);
+        assert_eq!(opt.delta, crate::delta::Mode::Rate);
+        assert_eq!(opt.keymap, vec![(0x0e, crate::keymap::Action::NextWindow)]);
+
+        let (_, wopt) = crate::parse_path("/does/not/exist", &opt).unwrap();
+        assert_eq!(wopt.interval, 2000);
+        assert!(!wopt.showlnum);
+        assert!(wopt.include.is_some());
+        let (_, wopt) = crate::parse_path("/does/not/exist:lnum", &opt).unwrap();
+        assert!(wopt.showlnum);
+
+        for s in [
+            "lnum = 1",
+            "unknown = true",
+            "[keys]\nx = \"no-such-action\"",
+            "[keys]\nxx = \"head\"",
+            "[path.\"/x\"]\noptions = \"fodl\"",
+            "fg = \"pink\"",
+            "interval = \"1h\"",
+        ] {
+            let ret = toml::from_str::<super::Config>(s)
+                .map_err(|e| e.into())
+                .and_then(|cfg| cfg.apply(&mut crate::Opt::default()));
+            assert!(ret.is_err(), "{s}");
+        }
+    }
+}
@@ -1,7 +1,14 @@
+use crate::buffer;
+use crate::delta;
+use crate::keymap;
+use crate::layout;
+use crate::process;
+use crate::session;
 use crate::util;
 use crate::window;
 use crate::Opt;
 use crate::Result;
+use crate::WindowOpt;
 
 #[cfg(feature = "curses")]
 use crate::curses as screen;
@@ -9,15 +16,92 @@ use crate::curses as screen;
 #[cfg(feature = "stdout")]
 use crate::stdout as screen;
 
+const DIR_WATCH_MASK: inotify::WatchMask = inotify::WatchMask::CREATE
+    .union(inotify::WatchMask::MOVED_TO)
+    .union(inotify::WatchMask::DELETE)
+    .union(inotify::WatchMask::MOVED_FROM);
+
+// Window locked on its own so that a slow window doesn't block the others,
+// with events to its thread locked separately so that posting one doesn't
+// wait for the window being repainted.
+#[derive(Debug)]
+struct WindowCell {
+    window: std::sync::Mutex<window::Window>,
+    event: std::sync::Mutex<WindowEvent>,
+    cv: std::sync::Condvar,
+}
+
+#[derive(Debug)]
+struct WindowEvent {
+    repaint: bool,
+    refresh: bool,      // interval elapsed or buffer selected
+    paths: Vec<String>, // paths to be updated before repaint
+    interval: std::time::Duration,
+    deadline: std::time::Instant, // next refresh
+    timed: bool,                  // refreshed every interval
+}
+
+impl WindowCell {
+    fn new(w: window::Window) -> Self {
+        let interval = std::time::Duration::from_millis(w.get_interval());
+        let timed = w.is_timed();
+        Self {
+            window: std::sync::Mutex::new(w),
+            event: std::sync::Mutex::new(WindowEvent {
+                repaint: true,
+                refresh: false,
+                paths: vec![],
+                interval,
+                deadline: std::time::Instant::now() + interval,
+                timed,
+            }),
+            cv: std::sync::Condvar::new(),
+        }
+    }
+
+    // Request repaint, after updating buffer for path if any.
+    fn post(&self, f: Option<&str>) {
+        let mut e = self.event.lock().unwrap();
+        e.repaint = true;
+        if let Some(f) = f {
+            if !e.paths.iter().any(|x| x == f) {
+                e.paths.push(f.to_string());
+            }
+        }
+        self.cv.notify_one();
+    }
+
+    // Request repaint after reading current buffer again if pseudo file,
+    // e.g. when it got selected.
+    fn post_refresh(&self) {
+        let mut e = self.event.lock().unwrap();
+        e.repaint = true;
+        e.refresh = true;
+        self.cv.notify_one();
+    }
+}
+
+type WindowRef = std::sync::Arc<WindowCell>;
+
 #[derive(Debug)]
 pub(crate) struct Container {
-    v: Vec<window::Window>,
+    v: Vec<WindowRef>,
     biv: Vec<usize>,
-    wih: std::collections::HashMap<i32, usize>,
+    wih: std::collections::HashMap<inotify::WatchDescriptor, Vec<(usize, String)>>,
+    dih: std::collections::HashMap<inotify::WatchDescriptor, Vec<(usize, String)>>,
+    gih: std::collections::HashMap<inotify::WatchDescriptor, Vec<(glob::Pattern, WindowOpt)>>,
     ci: usize,
+    pending: Option<u32>,
+    count: Option<usize>,
+    prompt: Option<(char, Vec<u8>)>,
+    keymap: std::collections::HashMap<u32, keymap::Action>,
+    layout: layout::Layout,
+    zoom: bool,
     attr: screen::Attr,
     inotify: inotify::Inotify,
-    is_interrupted: bool,
+    is_interrupted: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    thrv: Vec<std::thread::JoinHandle<()>>,
+    pgrep: Option<(String, u32)>, // process name and pid followed
 }
 
 impl Default for Container {
@@ -26,50 +110,394 @@ impl Default for Container {
             v: Vec::new(),
             biv: Vec::new(),
             wih: std::collections::HashMap::new(),
+            dih: std::collections::HashMap::new(),
+            gih: std::collections::HashMap::new(),
             ci: 0,
+            pending: None,
+            count: None,
+            prompt: None,
+            keymap: keymap::get_default_keymap(),
+            layout: layout::Layout::Window,
+            zoom: false,
             attr: screen::Attr::new(),
             inotify: inotify::Inotify::init().unwrap(),
-            is_interrupted: false,
+            is_interrupted: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            thrv: Vec::new(),
+            pgrep: None,
         }
     }
 }
 
 impl Container {
-    pub(crate) fn new(args: &[String], attr: screen::Attr, opt: &Opt) -> Result<Self> {
+    pub(crate) fn new(
+        args: &[(String, WindowOpt)],
+        session: Option<&session::Session>,
+        attr: screen::Attr,
+        opt: &Opt,
+    ) -> Result<Self> {
         let mut co = Self {
+            layout: opt.layout.clone(),
             attr,
+            pgrep: opt.pgrep.clone().zip(opt.pid),
             ..Default::default()
         };
-        co.init(args, opt)?;
+        co.keymap.extend(opt.keymap.iter().copied());
+        co.init(args, session, opt)?;
         Ok(co)
     }
 
-    fn init(&mut self, args: &[String], opt: &Opt) -> Result<()> {
-        self.build_window(None, opt)?;
-        for (i, f) in args.iter().enumerate() {
-            if !util::is_regular_file(f) {
-                log::info!("{}: No such regular file {}", util::function!(), f);
+    fn init(
+        &mut self,
+        args: &[(String, WindowOpt)],
+        session: Option<&session::Session>,
+        opt: &Opt,
+    ) -> Result<()> {
+        self.build_window()?;
+        // paths beyond number of windows go to buffer lists
+        let mut l = vec![];
+        if let Some(s) = session {
+            for (i, w) in s.window.iter().enumerate() {
+                for f in &w.paths {
+                    match crate::get_path_opt(f, opt) {
+                        Ok(v) => l.push((i % self.v.len(), f.clone(), v)),
+                        Err(e) => log::info!("{}: {}: {}", util::function!(), f, e),
+                    }
+                }
+            }
+        }
+        // directory or glob expands to its files, each in a window if fits
+        let mut a = vec![];
+        let mut grow = false;
+        for (f, wopt) in args {
+            let Some(g) = util::get_glob(f)? else {
+                a.push((f.clone(), wopt.clone()));
                 continue;
+            };
+            if let Err(e) = self.add_glob_watch(&g, wopt) {
+                log::info!("{}: {}: {}", util::function!(), g, e);
             }
-            if i < self.v.len() {
-                if let Err(e) = self.v[i].attach_buffer(f) {
+            for x in get_glob_paths(&g)? {
+                a.push((x, wopt.clone()));
+            }
+            grow = true;
+        }
+        while grow && self.v.len() < a.len() && self.add_window()? {}
+        for (i, (f, wopt)) in a.into_iter().enumerate() {
+            l.push((i % self.v.len(), f, wopt));
+        }
+        for (i, f, wopt) in &l {
+            if std::path::Path::new(f).exists() && !util::is_regular_file(f) && !util::is_fifo(f) {
+                log::info!("{}: No such regular file or FIFO {}", util::function!(), f);
+                continue;
+            }
+            if let Err(e) = self.attach_buffer(*i, f, wopt) {
+                log::info!("{}: {}", util::function!(), e);
+                break;
+            }
+        }
+        for w in &self.v {
+            w.window.lock().unwrap().goto_first_buffer()?;
+        }
+        if let Some(s) = session {
+            for (w, x) in self.v.iter().zip(&s.window) {
+                if let Err(e) = w.window.lock().unwrap().set_session(x) {
                     log::info!("{}: {}", util::function!(), e);
-                    break;
                 }
-                self.biv.push(i);
-                let wd = self
-                    .inotify
-                    .watches()
-                    .add(util::get_abspath(f)?, inotify::WatchMask::MODIFY)?;
-                self.wih.insert(wd.get_watch_descriptor_id(), i);
             }
+            if s.focus < self.v.len() {
+                self.ci = s.focus;
+            }
+        }
+        self.get_window(self.ci)
+            .focus(true, self.attr.get_standout_attr())
+    }
+
+    fn get_window(&self, i: usize) -> std::sync::MutexGuard<'_, window::Window> {
+        self.v[i].window.lock().unwrap()
+    }
+
+    fn notify(&self, i: usize) {
+        self.v[i].post(None);
+    }
+
+    fn notify_all(&self) {
+        for w in &self.v {
+            w.post(None);
+        }
+    }
+
+    // Wake up windows whose interval elapsed, and return time until the
+    // next one. Windows of files with inotify events are left as is.
+    pub(crate) fn update_timer(&self) -> Option<std::time::Duration> {
+        let now = std::time::Instant::now();
+        let mut next = None;
+        for w in &self.v {
+            let mut e = w.event.lock().unwrap();
+            if e.deadline <= now {
+                e.deadline = now + e.interval;
+                if e.timed {
+                    e.repaint = true;
+                    e.refresh = true;
+                    w.cv.notify_one();
+                }
+            }
+            let d = e.deadline - now;
+            next = Some(next.map_or(d, |x: std::time::Duration| x.min(d)));
+        }
+        next
+    }
+
+    // Attach path to window i, which is read first by its thread.
+    fn attach_buffer(&mut self, i: usize, f: &str, wopt: &WindowOpt) -> Result<()> {
+        self.get_window(i).attach_buffer(f, wopt)?;
+        self.v[i].post(Some(f));
+        if !self.biv.contains(&i) {
+            self.biv.push(i);
+            self.biv.sort_unstable();
+        }
+        self.add_watch(i, f)?;
+        self.add_dir_watch(i, f)
+    }
+
+    // Close current buffer of window i.
+    fn detach_buffer(&mut self, i: usize) -> Result<()> {
+        let f = self.get_window(i).get_path().to_string();
+        self.remove_watch(i, &f, false);
+        self.remove_watch(i, &f, true);
+        self.get_window(i).detach_buffer()?;
+        if self.get_window(i).is_dead() {
+            self.biv.retain(|&x| x != i);
+        }
+        Ok(())
+    }
+
+    // Unlink path f of window i from file (or parent directory) watches,
+    // and remove those no longer used by any window.
+    fn remove_watch(&mut self, i: usize, f: &str, dir: bool) {
+        let h = if dir { &mut self.dih } else { &mut self.wih };
+        for l in h.values_mut() {
+            l.retain(|x| x.0 != i || x.1 != f);
+        }
+        let mut v = vec![];
+        h.retain(|wd, l| {
+            if l.is_empty() {
+                v.push(wd.clone());
+            }
+            !l.is_empty()
+        });
+        for wd in v {
+            if dir && self.gih.contains_key(&wd) {
+                continue; // still watched for glob
+            }
+            if let Err(e) = self.inotify.watches().remove(wd) {
+                log::info!("{}: {}", util::function!(), e);
+            }
+        }
+    }
+
+    fn add_watch(&mut self, i: usize, f: &str) -> Result<()> {
+        self.remove_watch(i, f, false);
+        let abspath = util::get_abspath(f)?;
+        // pseudo file is refreshed every interval instead
+        if !util::is_regular_file(&abspath) || util::is_pseudo_file(&abspath) {
+            return Ok(());
+        }
+        let wd = self.inotify.watches().add(
+            abspath,
+            inotify::WatchMask::MODIFY
+                | inotify::WatchMask::ATTRIB
+                | inotify::WatchMask::CLOSE_WRITE
+                | inotify::WatchMask::DELETE_SELF
+                | inotify::WatchMask::MOVE_SELF,
+        )?;
+        self.wih.entry(wd).or_default().push((i, f.to_string()));
+        Ok(())
+    }
+
+    // watch parent directory for path to be (re)created
+    fn add_dir_watch(&mut self, i: usize, f: &str) -> Result<()> {
+        if util::get_command(f).is_some() || util::is_stdin(f) {
+            return Ok(());
+        }
+        let abspath = util::get_abspath(f)?;
+        let Some(d) = std::path::Path::new(&abspath).parent() else {
+            return Ok(());
+        };
+        let wd = match self.inotify.watches().add(d, DIR_WATCH_MASK) {
+            Ok(v) => v,
+            Err(e) => {
+                log::info!("{}: {:?} {}", util::function!(), d, e);
+                return Ok(());
+            }
+        };
+        self.dih.entry(wd).or_default().push((i, f.to_string()));
+        Ok(())
+    }
+
+    // watch parent directory of glob g for matching files to be created
+    fn add_glob_watch(&mut self, g: &str, wopt: &WindowOpt) -> Result<()> {
+        let p = glob::Pattern::new(g)?;
+        let Some(d) = std::path::Path::new(g).parent() else {
+            return Ok(());
+        };
+        let wd = self.inotify.watches().add(d, DIR_WATCH_MASK)?;
+        self.gih.entry(wd).or_default().push((p, wopt.clone()));
+        Ok(())
+    }
+
+    // Open file f created in directory watched for glob in an empty window,
+    // or a new window if it fits, otherwise in buffer list of current window.
+    fn open_created(&mut self, f: &str, wopt: &WindowOpt) -> Result<()> {
+        if self.v.iter().any(|w| w.window.lock().unwrap().has_path(f)) {
+            return Ok(());
+        }
+        let i = match (0..self.v.len()).find(|&i| self.get_window(i).is_dead()) {
+            Some(i) => i,
+            None if self.add_window()? => self.v.len() - 1,
+            None => self.ci,
+        };
+        log::info!("{}: {} in window {}", util::function!(), f, i);
+        self.attach_buffer(i, f, wopt)?;
+        self.notify(i);
+        Ok(())
+    }
+
+    // Post paths to be updated for event to their windows, and watch path
+    // created in parent directory.
+    fn handle_watch_event(
+        &mut self,
+        wd: &inotify::WatchDescriptor,
+        mask: inotify::EventMask,
+        name: Option<&std::ffi::OsStr>,
+    ) -> Result<bool> {
+        if mask.contains(inotify::EventMask::IGNORED) {
+            self.wih.remove(wd);
+            self.dih.remove(wd);
+            self.gih.remove(wd);
+            return Ok(false);
+        }
+        let created = mask.intersects(inotify::EventMask::CREATE | inotify::EventMask::MOVED_TO);
+        let l = if let Some(l) = self.wih.get(wd) {
+            l.clone()
+        } else if self.dih.contains_key(wd) || self.gih.contains_key(wd) {
+            let mut v = vec![];
+            for x in self.dih.get(wd).into_iter().flatten() {
+                if name.is_some()
+                    && name.and_then(std::ffi::OsStr::to_str)
+                        == Some(util::get_basename(&x.1)?.as_str())
+                {
+                    v.push(x.clone());
+                }
+            }
+            if created {
+                for (i, f) in &v {
+                    self.add_watch(*i, f)?;
+                }
+                if let Some(s) = name.and_then(std::ffi::OsStr::to_str) {
+                    self.open_glob_match(wd, s)?;
+                }
+            }
+            v
+        } else {
+            log::info!("{}: {:?} {:?} {:?}", util::function!(), wd, mask, name);
+            return Ok(false);
+        };
+        for (i, f) in &l {
+            self.v[*i].post(Some(f));
+        }
+        Ok(!l.is_empty())
+    }
+
+    // Open file s created in directory of wd if matching its globs.
+    fn open_glob_match(&mut self, wd: &inotify::WatchDescriptor, s: &str) -> Result<()> {
+        let mut v = vec![];
+        for (p, wopt) in self.gih.get(wd).into_iter().flatten() {
+            let Some(d) = std::path::Path::new(p.as_str()).parent() else {
+                continue;
+            };
+            let f = util::join_path(d.to_str().ok_or_else(util::error)?, s);
+            if p.matches(&f) && util::is_regular_file(&f) {
+                v.push((f, wopt.clone()));
+                break;
+            }
+        }
+        for (f, wopt) in &v {
+            self.open_created(f, wopt)?;
+        }
+        Ok(())
+    }
+
+    // Switch /proc/<pid> paths to process of the same name once exited.
+    pub(crate) fn follow_process(&mut self) -> Result<()> {
+        let Some((name, pid)) = self.pgrep.clone() else {
+            return Ok(());
+        };
+        if process::is_alive(pid) {
+            return Ok(());
+        }
+        let Some(x) = process::pgrep(&name)? else {
+            return Ok(());
+        };
+        log::info!("{}: {} {} -> {}", util::function!(), name, pid, x);
+        let prefix = format!("/proc/{pid}/");
+        let mut l = vec![];
+        for w in &self.v {
+            let w = w.window.lock().unwrap();
+            l.extend(w.get_paths().into_iter().filter(|f| f.starts_with(&prefix)));
+        }
+        for f in &l {
+            let g = format!("/proc/{x}/{}", &f[prefix.len()..]);
+            self.replace_path(f, &g)?;
+        }
+        self.pgrep = Some((name, x));
+        self.notify_all();
+        Ok(())
+    }
+
+    // Replace path f with g in windows, along with their watches.
+    fn replace_path(&mut self, f: &str, g: &str) -> Result<()> {
+        for i in 0..self.v.len() {
+            if !self.get_window(i).replace_path(f, g)? {
+                continue;
+            }
+            self.v[i].post(Some(g));
+            self.remove_watch(i, f, false);
+            self.remove_watch(i, f, true);
+            self.add_watch(i, g)?;
+            self.add_dir_watch(i, g)?;
         }
-        self.v[self.ci].focus(true, self.attr.get_standout_attr())
+        Ok(())
+    }
+
+    pub(crate) fn get_watch_fd(&self) -> i32 {
+        std::os::fd::AsRawFd::as_raw_fd(&self.inotify)
+    }
+
+    pub(crate) fn read_watch_events(&mut self) -> Result<()> {
+        let mut buf = [0; 4096];
+        let v = match self.inotify.read_events(&mut buf) {
+            Ok(v) => v,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => return Ok(()),
+            Err(e) => return Err(Box::new(e)),
+        };
+        let mut updated = false;
+        for event in v {
+            log::info!("{}: {:?}", util::function!(), event);
+            match self.handle_watch_event(&event.wd, event.mask, event.name) {
+                Ok(v) => updated |= v,
+                Err(e) => log::info!("{}: {}", util::function!(), e),
+            }
+        }
+        if updated {
+            screen::flash_terminal()?;
+        }
+        Ok(())
     }
 
     fn goto_next_window(&mut self) -> Result<()> {
+        self.set_zoom(false)?;
         let a = self.attr.get_standout_attr();
-        self.v[self.ci].focus(false, 0)?;
+        self.get_window(self.ci).focus(false, 0)?;
         for (i, &idx) in self.biv.iter().enumerate() {
             if idx == self.ci {
                 if idx == self.biv[self.biv.len() - 1] {
@@ -77,19 +505,20 @@ impl Container {
                 } else {
                     self.ci = self.biv[i + 1];
                 }
-                return self.v[self.ci].focus(true, a);
+                return self.get_window(self.ci).focus(true, a);
             }
         }
         if !self.biv.is_empty() {
             self.ci = self.biv[0];
-            return self.v[self.ci].focus(true, a);
+            return self.get_window(self.ci).focus(true, a);
         }
         Ok(())
     }
 
     fn goto_prev_window(&mut self) -> Result<()> {
+        self.set_zoom(false)?;
         let a = self.attr.get_standout_attr();
-        self.v[self.ci].focus(false, 0)?;
+        self.get_window(self.ci).focus(false, 0)?;
         for (i, &idx) in self.biv.iter().enumerate() {
             if idx == self.ci {
                 if idx == self.biv[0] {
@@ -97,92 +526,153 @@ impl Container {
                 } else {
                     self.ci = self.biv[i - 1];
                 }
-                return self.v[self.ci].focus(true, a);
+                return self.get_window(self.ci).focus(true, a);
             }
         }
         if !self.biv.is_empty() {
             self.ci = self.biv[self.biv.len() - 1];
-            return self.v[self.ci].focus(true, a);
+            return self.get_window(self.ci).focus(true, a);
         }
         Ok(())
     }
 
-    fn build_window(&mut self, cv: Option<&std::sync::Condvar>, opt: &Opt) -> Result<()> {
-        if !opt.rotatecol {
-            self.build_window_xy(cv, opt)
-        } else {
-            self.build_window_yx(cv, opt)
+    fn build_window(&mut self) -> Result<()> {
+        let yy = self.attr.get_terminal_lines();
+        let xx = self.attr.get_terminal_cols();
+        if self.zoom {
+            return self.alloc_window(self.ci, yy, xx, 0, 0);
+        }
+        let v = self.layout.get_geometry(yy, xx)?;
+        for (seq, (ylen, xlen, ypos, xpos)) in v.into_iter().enumerate() {
+            self.alloc_window(seq, ylen, xlen, ypos, xpos)?;
         }
+        Ok(())
     }
 
-    fn build_window_xy(&mut self, cv: Option<&std::sync::Condvar>, opt: &Opt) -> Result<()> {
-        let mut seq = 0;
-        let xx = self.attr.get_terminal_cols();
+    // Append window to layout if it fits, and start its thread if running.
+    fn add_window(&mut self) -> Result<bool> {
+        if self.zoom {
+            return Ok(false);
+        }
+        let prev = self.layout.clone();
+        self.layout.add_window();
         let yy = self.attr.get_terminal_lines();
-        let x = opt.layout.len();
-        let xq = xx / x;
-        let xr = xx % x;
-
-        for i in 0..x {
-            let xpos = xq * i;
-            let mut xlen = xq;
-            if i == x - 1 {
-                xlen += xr;
-            }
-            let mut y = opt.layout[i];
-            if y == 0 {
-                y = 1; // ignore invalid
-            }
-            let yq = yy / y;
-            let yr = yy % y;
-
-            for j in 0..y {
-                let ypos = yq * j;
-                let mut ylen = yq;
-                if j == y - 1 {
-                    ylen += yr;
-                }
-                self.alloc_window(seq, ylen, xlen, ypos, xpos, cv)?;
-                seq += 1;
+        let xx = self.attr.get_terminal_cols();
+        if self.layout.get_geometry(yy, xx).is_err() {
+            self.layout = prev;
+            return Ok(false);
+        }
+        self.build_window()?;
+        if !self.thrv.is_empty() {
+            self.spawn_thread(self.v.len() - 1, None);
+        }
+        Ok(true)
+    }
+
+    fn goto_window(&mut self, i: usize) -> Result<()> {
+        self.set_zoom(false)?;
+        self.get_window(self.ci).focus(false, 0)?;
+        self.ci = i;
+        self.get_window(self.ci)
+            .focus(true, self.attr.get_standout_attr())
+    }
+
+    // Select window next to current one on screen in direction of action,
+    // preferring one sharing the longest edge.
+    fn goto_adjacent_window(&mut self, a: keymap::Action) -> Result<()> {
+        self.set_zoom(false)?;
+        let (ylen, xlen, ypos, xpos) = self.get_window(self.ci).get_geometry();
+        let mut found: Option<(usize, usize)> = None;
+        for (i, w) in self.v.iter().enumerate() {
+            let (yl, xl, yp, xp) = w.window.lock().unwrap().get_geometry();
+            let n = match a {
+                keymap::Action::WindowLeft if xp + xl == xpos => get_overlap(yp, yl, ypos, ylen),
+                keymap::Action::WindowRight if xp == xpos + xlen => get_overlap(yp, yl, ypos, ylen),
+                keymap::Action::WindowUp if yp + yl == ypos => get_overlap(xp, xl, xpos, xlen),
+                keymap::Action::WindowDown if yp == ypos + ylen => get_overlap(xp, xl, xpos, xlen),
+                _ => 0,
+            };
+            if n > 0 && found.is_none_or(|x| n > x.0) {
+                found = Some((n, i));
             }
         }
-        Ok(())
+        match found {
+            Some((_, i)) => self.goto_window(i),
+            None => Ok(()),
+        }
     }
 
-    fn build_window_yx(&mut self, cv: Option<&std::sync::Condvar>, opt: &Opt) -> Result<()> {
-        let mut seq = 0;
+    // Reallocate windows whose geometry changed after layout update.
+    fn rebuild_window(&mut self) -> Result<bool> {
         let yy = self.attr.get_terminal_lines();
         let xx = self.attr.get_terminal_cols();
-        let y = opt.layout.len();
-        let yq = yy / y;
-        let yr = yy % y;
-
-        for i in 0..y {
-            let ypos = yq * i;
-            let mut ylen = yq;
-            if i == y - 1 {
-                ylen += yr;
-            }
-            let mut x = opt.layout[i];
-            if x == 0 {
-                x = 1; // ignore invalid
-            }
-            let xq = xx / x;
-            let xr = xx % x;
-
-            for j in 0..x {
-                let xpos = xq * j;
-                let mut xlen = xq;
-                if j == x - 1 {
-                    xlen += xr;
-                }
-                self.alloc_window(seq, ylen, xlen, ypos, xpos, cv)?;
-                seq += 1;
+        let Ok(v) = self.layout.get_geometry(yy, xx) else {
+            return Ok(false);
+        };
+        if self.zoom {
+            return Ok(true);
+        }
+        for (seq, (ylen, xlen, ypos, xpos)) in v.into_iter().enumerate() {
+            if self.get_window(seq).get_geometry() != (ylen, xlen, ypos, xpos) {
+                self.alloc_window(seq, ylen, xlen, ypos, xpos)?;
             }
         }
+        Ok(true)
+    }
+
+    fn resize_window(&mut self, d: f64) -> Result<()> {
+        let prev = self.layout.clone();
+        if !self.layout.resize_window(self.ci, d) {
+            return Ok(());
+        }
+        if !self.rebuild_window()? {
+            self.layout = prev;
+            return self.get_window(self.ci).set_status("Window too small");
+        }
         Ok(())
     }
 
+    // Swap buffers of current window with next window, and follow them.
+    fn swap_window(&mut self) -> Result<()> {
+        self.set_zoom(false)?;
+        if self.v.len() < 2 {
+            return Ok(());
+        }
+        let i = self.ci;
+        let j = (i + 1) % self.v.len();
+        self.get_window(i).focus(false, 0)?;
+        self.v.swap(i, j);
+        self.get_window(i).swap_screen(&mut self.get_window(j))?;
+        for l in self.wih.values_mut().chain(self.dih.values_mut()) {
+            for x in l {
+                if x.0 == i {
+                    x.0 = j;
+                } else if x.0 == j {
+                    x.0 = i;
+                }
+            }
+        }
+        self.biv = (0..self.v.len())
+            .filter(|&k| !self.get_window(k).is_dead())
+            .collect();
+        self.ci = j;
+        self.get_window(self.ci)
+            .focus(true, self.attr.get_standout_attr())
+    }
+
+    // Show current window in full screen while the others are hidden.
+    fn set_zoom(&mut self, t: bool) -> Result<()> {
+        if self.zoom == t {
+            return Ok(());
+        }
+        self.zoom = t;
+        for (i, w) in self.v.iter().enumerate() {
+            w.window.lock().unwrap().set_hidden(t && i != self.ci);
+        }
+        screen::clear_terminal()?;
+        self.build_window()
+    }
+
     fn alloc_window(
         &mut self,
         seq: usize,
@@ -190,15 +680,17 @@ impl Container {
         xlen: usize,
         ypos: usize,
         xpos: usize,
-        cv: Option<&std::sync::Condvar>,
     ) -> Result<()> {
         if self.v.get(seq).is_some() {
-            self.v[seq].resize(ylen, xlen, ypos, xpos, &mut self.attr)?;
-            assert!(cv.is_some());
-            cv.unwrap().notify_all();
+            self.v[seq]
+                .window
+                .lock()
+                .unwrap()
+                .resize(ylen, xlen, ypos, xpos, &mut self.attr)?;
+            self.notify(seq);
         } else {
-            self.v
-                .push(window::Window::new(ylen, xlen, ypos, xpos, &self.attr)?);
+            let w = window::Window::new(ylen, xlen, ypos, xpos, &self.attr)?;
+            self.v.push(std::sync::Arc::new(WindowCell::new(w)));
         }
         log::info!(
             "{}: seq {}, len({}, {}), pos({}, {})",
@@ -212,160 +704,424 @@ impl Container {
         Ok(())
     }
 
-    pub(crate) fn parse_event(&mut self, x: i32, cv: &std::sync::Condvar, opt: &Opt) -> Result<()> {
+    pub(crate) fn parse_event(&mut self, x: i32, opt: &Opt) -> Result<()> {
         if x == screen::KEY_ERR {
             //log::info!("{}: KEY_ERR", util::function!());
             return Ok(());
         }
         let x = u32::try_from(x)?;
-        if x == screen::KEY_RESIZE || x == screen::key_ctrl(u32::from('l')) {
-            screen::update_terminal_size(&mut self.attr)?;
-            screen::clear_terminal()?;
-            self.build_window(Some(cv), opt)?;
-        } else if x == u32::from('h') || x == screen::KEY_LEFT {
-            self.goto_prev_window()?;
-        } else if x == u32::from('l') || x == screen::KEY_RIGHT {
-            self.goto_next_window()?;
-        } else if x == u32::from('0') {
-            self.v[self.ci].goto_head();
-            cv.notify_all();
-        } else if x == u32::from('$') {
-            self.v[self.ci].goto_tail();
-            cv.notify_all();
-        } else if x == u32::from('k') || x == screen::KEY_UP {
-            self.v[self.ci].goto_current(-1)?;
-            cv.notify_all();
-        } else if x == u32::from('j') || x == screen::KEY_DOWN {
-            self.v[self.ci].goto_current(1)?;
-            cv.notify_all();
-        } else if x == screen::key_ctrl(u32::from('B')) {
-            self.v[self.ci].goto_current(-isize::try_from(self.attr.get_terminal_lines())?)?;
-            cv.notify_all();
-        } else if x == screen::key_ctrl(u32::from('U')) {
-            self.v[self.ci].goto_current(-isize::try_from(self.attr.get_terminal_lines())? / 2)?;
-            cv.notify_all();
-        } else if x == screen::key_ctrl(u32::from('F')) {
-            self.v[self.ci].goto_current(isize::try_from(self.attr.get_terminal_lines())?)?;
-            cv.notify_all();
-        } else if x == screen::key_ctrl(u32::from('D')) {
-            self.v[self.ci].goto_current(isize::try_from(self.attr.get_terminal_lines())? / 2)?;
-            cv.notify_all();
-        } else {
-            cv.notify_all();
+        if self.prompt.is_some() && x != screen::KEY_RESIZE {
+            return self.parse_prompt(x, opt);
+        }
+        self.get_window(self.ci).set_status("")?;
+        if x == screen::KEY_RESIZE {
+            return self.repaint();
+        }
+        let count = self.count.take();
+        if let Some(p) = self.pending.take() {
+            if let Some(a) = keymap::get_prefixed_action(p, x) {
+                return self.exec_action(a, count);
+            }
+            return Ok(());
+        }
+        // count prefix, where "0" is a count only if following other digits
+        if let Some(d) = char::from_u32(x).and_then(|c| c.to_digit(10)) {
+            if count.is_some() || (d != 0 && !self.keymap.contains_key(&x)) {
+                let n = count
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(usize::try_from(d)?);
+                self.count = Some(n);
+                return self.get_window(self.ci).set_status(&n.to_string());
+            }
+        }
+        let Some(&a) = self.keymap.get(&x) else {
+            if keymap::is_prefix(x) {
+                self.pending = Some(x);
+                self.count = count;
+                let k = if x == u32::from('g') { "g" } else { "^W" };
+                let n = count.map(|n| n.to_string()).unwrap_or_default();
+                return self.get_window(self.ci).set_status(&format!("{n}{k}"));
+            }
+            return Ok(());
+        };
+        self.exec_action(a, count)
+    }
+
+    fn exec_action(&mut self, a: keymap::Action, count: Option<usize>) -> Result<()> {
+        let n = isize::try_from(count.unwrap_or(1))?;
+        let cols = isize::try_from(self.get_window(self.ci).get_panel_xlen())?;
+        let lines = isize::try_from(self.attr.get_terminal_lines())?;
+        match a {
+            keymap::Action::Repaint => return self.repaint(),
+            keymap::Action::PrevWindow => return self.goto_prev_window(),
+            keymap::Action::NextWindow => return self.goto_next_window(),
+            keymap::Action::WindowLeft
+            | keymap::Action::WindowDown
+            | keymap::Action::WindowUp
+            | keymap::Action::WindowRight => return self.goto_adjacent_window(a),
+            keymap::Action::GotoWindow => match count {
+                Some(i) if (1..=self.v.len()).contains(&i) => self.goto_window(i - 1)?,
+                Some(i) => {
+                    return self
+                        .get_window(self.ci)
+                        .set_status(&format!("No window {i}"))
+                }
+                None => return self.goto_next_window(),
+            },
+            keymap::Action::PrevBuffer => {
+                self.get_window(self.ci).goto_buffer(-1)?;
+                self.v[self.ci].post_refresh();
+            }
+            keymap::Action::NextBuffer => {
+                self.get_window(self.ci).goto_buffer(1)?;
+                self.v[self.ci].post_refresh();
+            }
+            keymap::Action::GrowWindow => self.resize_window(0.1)?,
+            keymap::Action::ShrinkWindow => self.resize_window(-0.1)?,
+            keymap::Action::SwapWindow => self.swap_window()?,
+            keymap::Action::ZoomWindow => self.set_zoom(!self.zoom)?,
+            keymap::Action::Head => self.get_window(self.ci).goto_head()?,
+            keymap::Action::Tail => self.get_window(self.ci).goto_tail()?,
+            keymap::Action::Follow => {
+                let t = !self.get_window(self.ci).is_follow();
+                self.get_window(self.ci).set_follow(t)?;
+            }
+            keymap::Action::Table => {
+                let t = !self.get_window(self.ci).is_table();
+                self.get_window(self.ci).set_table(t)?;
+            }
+            keymap::Action::Delta => {
+                let mode = match self.get_window(self.ci).get_delta_mode() {
+                    delta::Mode::Raw => delta::Mode::Delta,
+                    delta::Mode::Delta => delta::Mode::Rate,
+                    delta::Mode::Rate => delta::Mode::Raw,
+                };
+                self.get_window(self.ci).set_delta_mode(mode)?;
+            }
+            keymap::Action::Up => self.get_window(self.ci).goto_current(-n)?,
+            keymap::Action::Down => self.get_window(self.ci).goto_current(n)?,
+            keymap::Action::Left => self.get_window(self.ci).goto_column(-cols / 2 * n),
+            keymap::Action::Right => self.get_window(self.ci).goto_column(cols / 2 * n),
+            keymap::Action::FirstColumn => self.get_window(self.ci).goto_first_column(),
+            keymap::Action::LastColumn => self.get_window(self.ci).goto_last_column(),
+            keymap::Action::SearchForward => return self.open_prompt('/'),
+            keymap::Action::SearchBackward => return self.open_prompt('?'),
+            keymap::Action::Filter => return self.open_prompt('&'),
+            keymap::Action::Command => return self.open_prompt(':'),
+            keymap::Action::NextMatch => self.get_window(self.ci).goto_match(true)?,
+            keymap::Action::PrevMatch => self.get_window(self.ci).goto_match(false)?,
+            keymap::Action::PageUp => self.get_window(self.ci).goto_current(-lines)?,
+            keymap::Action::HalfPageUp => self.get_window(self.ci).goto_current(-lines / 2)?,
+            keymap::Action::PageDown => self.get_window(self.ci).goto_current(lines)?,
+            keymap::Action::HalfPageDown => self.get_window(self.ci).goto_current(lines / 2)?,
+        }
+        match a {
+            keymap::Action::GrowWindow
+            | keymap::Action::ShrinkWindow
+            | keymap::Action::SwapWindow
+            | keymap::Action::ZoomWindow => self.notify_all(),
+            _ => self.notify(self.ci),
         }
         Ok(())
     }
 
-    pub(crate) fn set_interrupted(&mut self) {
-        self.is_interrupted = true;
-        log::info!("{}: interrupted", util::function!());
+    fn repaint(&mut self) -> Result<()> {
+        screen::update_terminal_size(&mut self.attr)?;
+        screen::clear_terminal()?;
+        self.build_window()
     }
 
-    pub(crate) fn is_interrupted(&self) -> bool {
-        self.is_interrupted
+    fn open_prompt(&mut self, k: char) -> Result<()> {
+        self.prompt = Some((k, vec![]));
+        self.get_window(self.ci).set_status(&k.to_string())
     }
-}
 
-fn thread_create_watch(
-    pair: &std::sync::Arc<(std::sync::Mutex<Container>, std::sync::Condvar)>,
-) -> std::thread::JoinHandle<()> {
-    let pair = std::sync::Arc::clone(pair);
-    std::thread::spawn(move || {
-        let tid = std::thread::current().id();
-        let (co, cv) = &*pair;
-        loop {
-            let mut buf = [0; 1024];
-            let mut co = co.lock().unwrap();
-            match co.inotify.read_events(&mut buf) {
-                Ok(v) => {
-                    for event in v {
-                        if let Some(&i) = co.wih.get(&event.wd.get_watch_descriptor_id()) {
-                            log::info!("{:?} watch {} {:?}", tid, i, co.wih);
-                            co.v[i].update_buffer().unwrap();
-                        } else {
-                            log::info!("{:?} {:?}", tid, event);
-                            return;
-                        }
-                    }
-                    screen::flash_terminal();
+    // Edit prompt line shown on bottom border of current window.
+    fn parse_prompt(&mut self, x: u32, opt: &Opt) -> Result<()> {
+        let Some((k, mut b)) = self.prompt.take() else {
+            return Ok(());
+        };
+        if x == 0x1B {
+            return self.get_window(self.ci).set_status("");
+        } else if x == u32::from('\n') || x == u32::from('\r') || x == screen::KEY_ENTER {
+            self.get_window(self.ci).set_status("")?;
+            let s = String::from_utf8_lossy(&b).to_string();
+            return self.exec_prompt(k, &s, opt);
+        } else if x == screen::KEY_BACKSPACE || x == 0x7F || x == 0x08 {
+            if b.is_empty() {
+                return self.get_window(self.ci).set_status("");
+            }
+            while let Some(c) = b.pop() {
+                if c & 0xC0 != 0x80 {
+                    break;
                 }
-                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => (),
-                Err(e) => {
-                    log::info!("{:?} {}", tid, e);
-                    return;
+            }
+        } else if let Ok(c) = u8::try_from(x) {
+            if c >= 0x20 {
+                b.push(c);
+            }
+        }
+        self.get_window(self.ci)
+            .set_status(&format!("{k}{}", String::from_utf8_lossy(&b)))?;
+        self.prompt = Some((k, b));
+        Ok(())
+    }
+
+    fn exec_prompt(&mut self, k: char, s: &str, opt: &Opt) -> Result<()> {
+        match k {
+            '/' => self.get_window(self.ci).set_search(s, true)?,
+            '?' => self.get_window(self.ci).set_search(s, false)?,
+            '&' => self.get_window(self.ci).set_filter(s)?,
+            ':' => self.exec_command(s, opt)?,
+            _ => (),
+        }
+        self.notify(self.ci);
+        Ok(())
+    }
+
+    // ex style command, i.e. "e[dit] <path>", "bn[ext]", "bp[revious]",
+    // "bd[elete]", "mks[ession] [<path>]" or "q[uit]"
+    fn exec_command(&mut self, s: &str, opt: &Opt) -> Result<()> {
+        let s = s.trim();
+        let (cmd, arg) = match s.split_once(char::is_whitespace) {
+            Some((a, b)) => (a, b.trim_start()),
+            None => (s, ""),
+        };
+        match cmd {
+            "" => Ok(()),
+            "e" | "edit" => self.edit_path(arg, opt),
+            "bn" | "bnext" => {
+                self.get_window(self.ci).goto_buffer(1)?;
+                self.v[self.ci].post_refresh();
+                Ok(())
+            }
+            "bp" | "bprevious" => {
+                self.get_window(self.ci).goto_buffer(-1)?;
+                self.v[self.ci].post_refresh();
+                Ok(())
+            }
+            "bd" | "bdelete" => {
+                if self.get_window(self.ci).is_dead() {
+                    return self.get_window(self.ci).set_status("No buffer");
+                }
+                self.detach_buffer(self.ci)
+            }
+            "mks" | "mksession" => {
+                let f = if arg.is_empty() {
+                    opt.session.as_deref()
+                } else {
+                    Some(arg)
+                };
+                let Some(f) = f else {
+                    return self.get_window(self.ci).set_status("No session file");
+                };
+                match self.save_session(f) {
+                    Ok(()) => self
+                        .get_window(self.ci)
+                        .set_status(&format!("Session saved to {f}")),
+                    Err(e) => self.get_window(self.ci).set_status(&format!("{f}: {e}")),
                 }
-            };
-            let ret = cv
-                .wait_timeout(co, std::time::Duration::from_secs(1))
-                .unwrap();
-            if ret.0.is_interrupted() {
-                log::info!("{:?} watch interrupted", tid);
-                break;
             }
+            "q" | "quit" => {
+                self.set_interrupted();
+                Ok(())
+            }
+            _ => self
+                .get_window(self.ci)
+                .set_status(&format!("Not a command: {cmd}")),
         }
-    })
-}
+    }
 
-fn thread_create_window(
-    pair: &std::sync::Arc<(std::sync::Mutex<Container>, std::sync::Condvar)>,
-    opt: &Opt,
-) -> Vec<std::thread::JoinHandle<()>> {
-    let mut thrv = Vec::new();
-    let (co, _) = &**pair;
-    let n = co.lock().unwrap().v.len();
-
-    for i in 0..n {
-        let sinterval = opt.sinterval;
-        let minterval = opt.minterval;
-        let showlnum = opt.showlnum;
-        let foldline = opt.foldline;
-        let blinkline = opt.blinkline;
-        let usedelay = opt.usedelay;
-        let pair = std::sync::Arc::clone(pair);
-        thrv.push(std::thread::spawn(move || {
-            let tid = std::thread::current().id();
-            let (co, cv) = &*pair;
-            let t = sinterval * 1000 + minterval;
-            let mut d = t;
-            if usedelay {
-                let r: u64 = rand::prelude::random();
-                d = r % 1000;
+    // Open path in current window if empty, otherwise in the first empty
+    // window, otherwise add to buffer list of current window.
+    fn edit_path(&mut self, arg: &str, opt: &Opt) -> Result<()> {
+        if arg.is_empty() {
+            return self.get_window(self.ci).set_status("No file name");
+        }
+        let (f, wopt) = match crate::parse_path(arg, opt) {
+            Ok(v) => v,
+            Err(e) => return self.get_window(self.ci).set_status(&e.to_string()),
+        };
+        if std::path::Path::new(&f).exists() {
+            // FIFO not opened here, as it blocks until opened by writer
+            if util::is_regular_file(&f) {
+                if let Err(e) = std::fs::File::open(&f) {
+                    return self.get_window(self.ci).set_status(&format!("{f}: {e}"));
+                }
+            } else if !util::is_fifo(&f) {
+                return self
+                    .get_window(self.ci)
+                    .set_status(&format!("Not a regular file or FIFO: {f}"));
             }
+        }
+        if self.get_window(self.ci).goto_path(&f)? {
+            self.v[self.ci].post_refresh();
+            return Ok(());
+        }
+        let i = if self.zoom || self.get_window(self.ci).is_dead() {
+            self.ci
+        } else if let Some(i) = self
+            .v
+            .iter()
+            .position(|w| w.window.lock().unwrap().is_dead())
+        {
+            i
+        } else {
+            self.ci
+        };
+        if let Err(e) = self.attach_buffer(i, &f, &wopt) {
+            return self.get_window(self.ci).set_status(&format!("{f}: {e}"));
+        }
+        if i != self.ci {
+            self.get_window(self.ci).focus(false, 0)?;
+            self.ci = i;
+            self.get_window(self.ci)
+                .focus(true, self.attr.get_standout_attr())?;
+        }
+        Ok(())
+    }
+
+    fn save_session(&self, f: &str) -> Result<()> {
+        let mut window = vec![];
+        for w in &self.v {
+            window.push(w.window.lock().unwrap().get_session()?);
+        }
+        let s = session::Session {
+            layout: self.layout.to_string(),
+            rotate: self.layout.is_rotated(),
+            focus: self.ci,
+            window,
+        };
+        session::save(&s, f)
+    }
+
+    // Start thread of window i, whose first refresh is after delay if any,
+    // otherwise after its interval.
+    fn spawn_thread(&mut self, i: usize, delay: Option<u64>) {
+        let a = self.attr.get_standout_attr();
+        let b = self.attr.get_search_attr();
+        let w = std::sync::Arc::clone(&self.v[i]);
+        let (t, timed) = {
+            let x = w.window.lock().unwrap();
+            (x.get_interval(), x.is_timed())
+        };
+        let d = delay.unwrap_or(t);
+        {
+            let mut e = w.event.lock().unwrap();
+            e.interval = std::time::Duration::from_millis(t);
+            e.timed = timed;
+            e.deadline = std::time::Instant::now() + std::time::Duration::from_millis(d);
+        }
+        let is_interrupted = std::sync::Arc::clone(&self.is_interrupted);
+        self.thrv.push(std::thread::spawn(move || {
+            let tid = std::thread::current().id();
             loop {
-                let mut co = co.lock().unwrap();
-                let a = co.attr.get_standout_attr();
-                co.v[i].repaint(showlnum, foldline, blinkline, a).unwrap();
-                let ret = cv
-                    .wait_timeout(co, std::time::Duration::from_millis(d))
-                    .unwrap();
-                if ret.0.is_interrupted() {
+                let (paths, refresh) = {
+                    let mut e = w.event.lock().unwrap();
+                    while !e.repaint && !is_interrupted.load(std::sync::atomic::Ordering::SeqCst) {
+                        e = w.cv.wait(e).unwrap();
+                    }
+                    e.repaint = false;
+                    (std::mem::take(&mut e.paths), std::mem::take(&mut e.refresh))
+                };
+                if is_interrupted.load(std::sync::atomic::Ordering::SeqCst) {
                     log::info!("{:?} window interrupted", tid);
                     break;
                 }
-                d = t;
+                // pseudo files are read and commands run without the window
+                // lock, so that the event loop isn't blocked meanwhile
+                let l = w.window.lock().unwrap().get_fetch_list(&paths, refresh);
+                let l: Vec<_> = l
+                    .into_iter()
+                    .map(|(f, t)| {
+                        let x = buffer::fetch(&f, t);
+                        (f, x)
+                    })
+                    .collect();
+                let mut x = w.window.lock().unwrap();
+                for (f, r) in l {
+                    x.set_fetched(&f, r);
+                }
+                for f in &paths {
+                    if let Err(e) = x.update_buffer(f) {
+                        log::info!("{:?} {}", tid, e);
+                    }
+                }
+                if refresh && !paths.iter().any(|f| f == x.get_path()) {
+                    if let Err(e) = x.refresh_buffer() {
+                        log::info!("{:?} {}", tid, e);
+                    }
+                }
+                x.repaint(a, b).unwrap();
+                // interval changes with buffer
+                let t = std::time::Duration::from_millis(x.get_interval());
+                let timed = x.is_timed();
+                drop(x);
+                let mut e = w.event.lock().unwrap();
+                e.interval = t;
+                e.timed = timed;
             }
         }));
     }
-    thrv
+
+    pub(crate) fn set_interrupted(&mut self) {
+        self.is_interrupted
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        // under lock so that window threads don't miss notification
+        for w in &self.v {
+            let _e = w.event.lock().unwrap();
+            w.cv.notify_one();
+        }
+        log::info!("{}: interrupted", util::function!());
+    }
+
+    pub(crate) fn is_interrupted(&self) -> bool {
+        self.is_interrupted
+            .load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+// length of overlap between ranges [a, a+alen) and [b, b+blen)
+fn get_overlap(a: usize, alen: usize, b: usize, blen: usize) -> usize {
+    (a + alen).min(b + blen).saturating_sub(a.max(b))
+}
+
+// regular files matching glob g in sorted order
+fn get_glob_paths(g: &str) -> Result<Vec<String>> {
+    let mut v = vec![];
+    for x in glob::glob(g)? {
+        let f = x?
+            .into_os_string()
+            .into_string()
+            .map_err(|_| util::error())?;
+        if util::is_regular_file(&f) {
+            v.push(f);
+        }
+    }
+    Ok(v)
 }
 
-// XXX Threads lock the entire container, whereas in C++ / Go they only
-// lock shared resource, i.e. terminal size and buffers.
-pub(crate) fn thread_create(
-    pair: &std::sync::Arc<(std::sync::Mutex<Container>, std::sync::Condvar)>,
-    opt: &Opt,
-) -> Vec<std::thread::JoinHandle<()>> {
-    let mut thrv = Vec::new();
-    thrv.push(thread_create_watch(pair));
-    thrv.extend(thread_create_window(pair, opt));
-    for thr in &thrv {
+// Each thread only locks its own window, and repaints when posted by the
+// event loop, i.e. on key input, file update or its interval elapsed.
+pub(crate) fn thread_create(co: &mut Container, opt: &Opt) {
+    for i in 0..co.v.len() {
+        // the first refresh after random delay if enabled
+        let d = if opt.usedelay {
+            let r: u64 = rand::prelude::random();
+            Some(r % 1000)
+        } else {
+            None
+        };
+        co.spawn_thread(i, d);
+    }
+    for thr in &co.thrv {
         log::info!("{}: {:?}", util::function!(), thr.thread().id());
     }
-    thrv
 }
 
-pub(crate) fn thread_join(thrv: &mut Vec<std::thread::JoinHandle<()>>) {
-    while let Some(thr) = thrv.pop() {
+pub(crate) fn thread_join(co: &mut Container) {
+    while let Some(thr) = co.thrv.pop() {
         log::info!("{}: {:?}", util::function!(), thr.thread().id());
         thr.join().unwrap();
     }
@@ -13,6 +13,8 @@ pub(crate) const KEY_UP: u32 = 0o403;
 pub(crate) const KEY_DOWN: u32 = 0o402;
 pub(crate) const KEY_LEFT: u32 = 0o404;
 pub(crate) const KEY_RIGHT: u32 = 0o405;
+pub(crate) const KEY_BACKSPACE: u32 = 0o407;
+pub(crate) const KEY_ENTER: u32 = 0o527;
 pub(crate) const KEY_RESIZE: u32 = 0o632;
 
 // taken from /usr/include/curses.h
@@ -36,6 +38,7 @@ pub(crate) struct Attr {
     cols: usize,
     color_attr: u32,
     standout_attr: u32,
+    search_attr: u32,
 }
 
 impl Attr {
@@ -60,6 +63,10 @@ impl Attr {
     pub(crate) fn get_standout_attr(&self) -> u32 {
         self.standout_attr
     }
+
+    pub(crate) fn get_search_attr(&self) -> u32 {
+        self.search_attr
+    }
 }
 
 #[derive(Debug)]
@@ -95,12 +102,14 @@ pub(crate) fn string_to_color(arg: &str) -> i16 {
 }
 
 pub(crate) fn init_screen(fgcolor: i16, bgcolor: i16) -> Result<Attr> {
+    ncurses::setlocale(ncurses::LcCategory::all, "");
     ncurses::initscr();
     ncurses::keypad(ncurses::stdscr(), true);
     ncurses::noecho();
     ncurses::cbreak();
     ncurses::curs_set(ncurses::CURSOR_VISIBILITY::CURSOR_INVISIBLE).ok_or_else(util::error)?;
-    ncurses::wtimeout(ncurses::stdscr(), 500);
+    ncurses::wtimeout(ncurses::stdscr(), 0); // main loop polls input fd
+    ncurses::set_escdelay(25);
     clear_terminal()?;
 
     let mut attr = Attr::new();
@@ -117,6 +126,7 @@ pub(crate) fn init_screen(fgcolor: i16, bgcolor: i16) -> Result<Attr> {
         Ok(v) if v == "screen" => ncurses::A_REVERSE(),
         _ => ncurses::A_STANDOUT(),
     };
+    attr.search_attr = ncurses::A_BOLD() | ncurses::A_UNDERLINE();
     Ok(attr)
 }
 
@@ -126,10 +136,15 @@ pub(crate) fn cleanup_screen() -> Result<()> {
     Ok(())
 }
 
+// KEY_ERR if no input
 pub(crate) fn read_incoming() -> i32 {
     ncurses::wgetch(ncurses::stdscr())
 }
 
+pub(crate) fn get_input_fd() -> Option<i32> {
+    Some(libc::STDIN_FILENO)
+}
+
 pub(crate) fn clear_terminal() -> Result<()> {
     let _mtx = MTX.lock()?;
     ncurses::wclear(ncurses::stdscr());
@@ -137,8 +152,10 @@ pub(crate) fn clear_terminal() -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn flash_terminal() {
+pub(crate) fn flash_terminal() -> Result<()> {
+    let _mtx = MTX.lock()?;
     ncurses::flash();
+    Ok(())
 }
 
 pub(crate) fn alloc_screen(ylen: usize, xlen: usize, ypos: usize, xpos: usize) -> Result<Screen> {
@@ -187,7 +204,13 @@ impl Screen {
             ncurses::A_NORMAL()
         };
         ncurses::wattron(self.win, attr);
-        ncurses::mvwprintw(self.win, y.try_into()?, x.try_into()?, s);
+        // replace % with %%
+        ncurses::mvwprintw(
+            self.win,
+            y.try_into()?,
+            x.try_into()?,
+            &s.replace('%', "%%"),
+        );
         ncurses::wattroff(self.win, attr);
         Ok(())
     }
@@ -204,6 +227,20 @@ impl Screen {
         Ok(())
     }
 
+    pub(crate) fn erase_line(&mut self, y: usize) -> Result<()> {
+        let _mtx = MTX.lock()?;
+        ncurses::wmove(self.win, y.try_into()?, 0);
+        ncurses::wclrtoeol(self.win);
+        Ok(())
+    }
+
+    // Mark lines unchanged so that refresh doesn't output them.
+    pub(crate) fn untouch_lines(&mut self, y: usize, n: usize) -> Result<()> {
+        let _mtx = MTX.lock()?;
+        ncurses::wtouchln(self.win, y.try_into()?, n.try_into()?, 0);
+        Ok(())
+    }
+
     pub(crate) fn resize(&mut self, ylen: usize, xlen: usize) -> Result<()> {
         let _mtx = MTX.lock()?;
         ncurses::wresize(self.win, ylen.try_into()?, xlen.try_into()?);
@@ -0,0 +1,219 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Mode {
+    Raw,
+    Delta, // difference from previous refresh
+    Rate,  // difference per second
+}
+
+// line with numeric tokens removed, and values of those tokens
+type Snapshot = (String, Vec<i128>);
+
+#[derive(Debug)]
+pub(crate) struct Delta {
+    mode: Mode,
+    prev: std::collections::HashMap<usize, Snapshot>,
+    next: std::collections::HashMap<usize, Snapshot>,
+    generation: Option<u64>, // of buffer contents snapshot taken for
+    time: Option<std::time::Instant>,
+    elapsed: f64,
+}
+
+impl Delta {
+    pub(crate) fn new() -> Self {
+        Self {
+            mode: Mode::Raw,
+            prev: std::collections::HashMap::new(),
+            next: std::collections::HashMap::new(),
+            generation: None,
+            time: None,
+            elapsed: 0.0,
+        }
+    }
+
+    pub(crate) fn get_mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub(crate) fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.prev.clear();
+        self.next.clear();
+        self.generation = None;
+        self.time = None;
+    }
+
+    // Start a new snapshot if buffer contents were read again since the
+    // last one, called once per repaint with (generation, time) of buffer.
+    // Returns true if so, and then caller records every line of buffer, so
+    // that lines scrolled into view later are compared too. Otherwise
+    // repaint renders the same differences as before.
+    pub(crate) fn begin(&mut self, generation: (u64, std::time::Instant)) -> bool {
+        let (g, now) = generation;
+        if self.mode == Mode::Raw || self.generation == Some(g) {
+            return false;
+        }
+        self.generation = Some(g);
+        self.elapsed = match self.time {
+            Some(t) => now.duration_since(t).as_secs_f64(),
+            None => 0.0,
+        };
+        self.time = Some(now);
+        self.prev = std::mem::take(&mut self.next);
+        true
+    }
+
+    // Keep line pos for the next snapshot.
+    pub(crate) fn record(&mut self, pos: usize, s: &str) {
+        let (k, v) = split_line(s);
+        self.next.insert(pos, (k, v.iter().map(|x| x.2).collect()));
+    }
+
+    // Replace numeric tokens of line pos with difference from previous
+    // snapshot, or return s as is if not comparable.
+    pub(crate) fn format(&self, pos: usize, s: &str) -> String {
+        if self.mode == Mode::Raw {
+            return s.to_string();
+        }
+        let (k, v) = split_line(s);
+        match self.prev.get(&pos) {
+            Some(p) if p.0 == k && p.1.len() == v.len() => {
+                let rate = match self.mode {
+                    Mode::Rate if self.elapsed <= 0.0 => return s.to_string(),
+                    Mode::Rate => Some(self.elapsed),
+                    _ => None,
+                };
+                let d = v.iter().zip(&p.1).map(|(a, b)| a.2 - b);
+                format_line(s, &v, d, rate)
+            }
+            _ => s.to_string(),
+        }
+    }
+}
+
+fn is_number(s: &str) -> bool {
+    let t = s.strip_prefix('-').unwrap_or(s);
+    !t.is_empty() && t.bytes().all(|c| c.is_ascii_digit())
+}
+
+// line with numeric tokens removed, and those tokens as (start, end, value)
+fn split_line(s: &str) -> (String, Vec<(usize, usize, i128)>) {
+    let mut k = String::new();
+    let mut v = vec![];
+    let mut i = 0;
+    for t in s.split_inclusive(char::is_whitespace) {
+        let w = t.trim_end_matches(char::is_whitespace);
+        if let Some(x) = w.parse::<i128>().ok().filter(|_| is_number(w)) {
+            v.push((i, i + w.len(), x));
+            k.push_str(&t[w.len()..]);
+        } else {
+            k.push_str(t);
+        }
+        i += t.len();
+    }
+    (k, v)
+}
+
+// Replace tokens v of s with d, right aligned within original token width.
+fn format_line<I: Iterator<Item = i128>>(
+    s: &str,
+    v: &[(usize, usize, i128)],
+    d: I,
+    rate: Option<f64>,
+) -> String {
+    let mut ret = String::new();
+    let mut i = 0;
+    for (&(a, b, _), x) in v.iter().zip(d) {
+        ret.push_str(&s[i..a]);
+        let t = match rate {
+            Some(e) => {
+                let r = x as f64 / e;
+                if r.abs() >= 1000.0 || r.fract() == 0.0 {
+                    format!("{r:.0}")
+                } else {
+                    format!("{r:.1}")
+                }
+            }
+            None => x.to_string(),
+        };
+        // take room from preceding spaces to keep right edge if longer
+        let w = b - a;
+        let n = ret.len() - ret.trim_end_matches(' ').len();
+        let n = t.len().saturating_sub(w).min(n.saturating_sub(1));
+        ret.truncate(ret.len() - n);
+        ret.push_str(&format!("{t:>w$}"));
+        i = b;
+    }
+    ret.push_str(&s[i..]);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    // begin() at generation g of buffer with lines l
+    fn update(d: &mut super::Delta, g: u64, t: std::time::Instant, l: &[&str]) {
+        if d.begin((g, t)) {
+            for (pos, s) in l.iter().enumerate() {
+                d.record(pos, s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        let t = std::time::Instant::now();
+        let mut d = super::Delta::new();
+        d.set_mode(super::Mode::Delta);
+        update(
+            &mut d,
+            1,
+            t,
+            &["eth0:  100   20 cpu0", "nr_free_pages 5000"],
+        );
+        assert_eq!(d.format(0, "eth0:  100   20 cpu0"), "eth0:  100   20 cpu0");
+        assert_eq!(d.format(1, "nr_free_pages 5000"), "nr_free_pages 5000");
+        // line 1 compared without having been rendered
+        update(
+            &mut d,
+            2,
+            t,
+            &["eth0:  150   20 cpu0", "nr_free_pages 4000"],
+        );
+        assert_eq!(d.format(0, "eth0:  150   20 cpu0"), "eth0:   50    0 cpu0");
+        // repaint without refresh
+        update(
+            &mut d,
+            2,
+            t,
+            &["eth0:  150   20 cpu0", "nr_free_pages 4000"],
+        );
+        assert_eq!(d.format(0, "eth0:  150   20 cpu0"), "eth0:   50    0 cpu0");
+        assert_eq!(d.format(1, "nr_free_pages 4000"), "nr_free_pages -1000");
+        update(
+            &mut d,
+            3,
+            t,
+            &["eth1:  150   20 cpu0", "nr_free_pages 4000 x"],
+        );
+        assert_eq!(d.format(0, "eth1:  150   20 cpu0"), "eth1:  150   20 cpu0");
+        assert_eq!(d.format(1, "nr_free_pages 4000 x"), "nr_free_pages 4000 x");
+
+        d.set_mode(super::Mode::Rate);
+        update(&mut d, 3, t, &["a 10"]);
+        assert_eq!(d.format(0, "a 10"), "a 10");
+        let t = t + std::time::Duration::from_secs(2);
+        update(&mut d, 4, t, &["a 20"]);
+        assert_eq!(d.format(0, "a 20"), "a  5");
+        update(&mut d, 4, t, &["a 20"]);
+        assert_eq!(d.format(0, "a 20"), "a  5");
+        let t = t + std::time::Duration::from_secs(2);
+        update(&mut d, 5, t, &["a 25"]);
+        assert_eq!(d.format(0, "a 25"), "a 2.5");
+        let t = t + std::time::Duration::from_secs(2);
+        update(&mut d, 6, t, &["a 11025"]);
+        assert_eq!(d.format(0, "a 11025"), "a  5500");
+
+        d.set_mode(super::Mode::Raw);
+        assert!(!d.begin((7, t)));
+        assert_eq!(d.format(0, "a 11025"), "a 11025");
+    }
+}
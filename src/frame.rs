@@ -1,5 +1,6 @@
 use crate::panel;
 use crate::panel::PanelImpl;
+use crate::util;
 use crate::Result;
 
 #[cfg(feature = "curses")]
@@ -17,6 +18,10 @@ pub(crate) struct Frame {
     xpos: usize,
     title: String,
     focus: bool,
+    standout_attr: u32,
+    clip: (bool, bool),
+    status: String,
+    hidden: bool,
 }
 
 impl Drop for Frame {
@@ -41,6 +46,10 @@ impl panel::PanelImpl for Frame {
             xpos,
             title: String::new(),
             focus: false,
+            standout_attr: 0,
+            clip: (false, false),
+            status: String::new(),
+            hidden: false,
         };
         frame.scr.bkgd(attr.get_color_attr())?;
         frame.scr.r#box()?;
@@ -65,15 +74,23 @@ impl panel::PanelImpl for Frame {
 
     fn set_title(&mut self, s: &str) -> Result<()> {
         self.title = s.to_string();
-        self.print_title(0)
+        self.print_title(self.standout_attr, false)
     }
 
     fn set_focus(&mut self, t: bool, standout_attr: u32) -> Result<()> {
         self.focus = t;
-        self.print_title(standout_attr)
+        self.standout_attr = standout_attr;
+        self.print_title(standout_attr, false)
+    }
+
+    fn set_hidden(&mut self, t: bool) {
+        self.hidden = t;
     }
 
     fn refresh(&mut self) -> Result<()> {
+        if self.hidden {
+            return Ok(());
+        }
         self.scr.refresh()
     }
 
@@ -95,8 +112,7 @@ impl panel::PanelImpl for Frame {
         self.xpos = xpos;
         self.scr.resize(self.ylen, self.xlen)?;
         self.scr.r#move(self.ypos, self.xpos)?;
-        self.scr.r#box()?;
-        self.print_title(attr.get_standout_attr())
+        self.print_title(attr.get_standout_attr(), true)
     }
 
     fn print(&self, y: usize, x: usize, standout: bool, standout_attr: u32, s: &str) -> Result<()> {
@@ -105,8 +121,43 @@ impl panel::PanelImpl for Frame {
 }
 
 impl Frame {
-    fn print_title(&mut self, standout_attr: u32) -> Result<()> {
-        self.print(0, 1, self.focus, standout_attr, &self.title)?;
+    // mark bottom border if content is clipped on the left and/or right
+    pub(crate) fn set_clip(&mut self, left: bool, right: bool) -> Result<()> {
+        if self.clip == (left, right) {
+            return Ok(());
+        }
+        self.clip = (left, right);
+        self.print_title(self.standout_attr, false)
+    }
+
+    // print prompt or message on bottom border
+    pub(crate) fn set_status(&mut self, s: &str) -> Result<()> {
+        if self.status == s {
+            return Ok(());
+        }
+        self.status = s.to_string();
+        self.print_title(self.standout_attr, false)
+    }
+
+    // Only top and bottom rows are output unless full, as blank rows between
+    // them would overwrite panel.
+    fn print_title(&mut self, standout_attr: u32, full: bool) -> Result<()> {
+        self.scr.r#box()?; // clear previous title
+        if self.clip.0 {
+            self.print(self.ylen - 1, 1, false, 0, "<")?;
+        }
+        if self.clip.1 {
+            self.print(self.ylen - 1, self.xlen - 2, false, 0, ">")?;
+        }
+        if !self.status.is_empty() {
+            let s = util::truncate_width(&self.status, self.xlen.saturating_sub(2));
+            self.print(self.ylen - 1, 1, false, 0, s)?;
+        }
+        let s = util::truncate_width(&self.title, self.xlen.saturating_sub(2));
+        self.print(0, 1, self.focus, standout_attr, s)?;
+        if !full {
+            self.scr.untouch_lines(1, self.ylen.saturating_sub(2))?;
+        }
         self.refresh()
     }
 }
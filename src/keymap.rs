@@ -0,0 +1,206 @@
+#[cfg(feature = "curses")]
+use crate::curses as screen;
+
+#[cfg(feature = "stdout")]
+use crate::stdout as screen;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Action {
+    Repaint,
+    PrevWindow,
+    NextWindow,
+    PrevBuffer,
+    NextBuffer,
+    WindowLeft,
+    WindowDown,
+    WindowUp,
+    WindowRight,
+    GotoWindow,
+    GrowWindow,
+    ShrinkWindow,
+    SwapWindow,
+    ZoomWindow,
+    Head,
+    Tail,
+    Follow,
+    Table,
+    Delta,
+    Up,
+    Down,
+    Left,
+    Right,
+    FirstColumn,
+    LastColumn,
+    SearchForward,
+    SearchBackward,
+    NextMatch,
+    PrevMatch,
+    Filter,
+    Command,
+    PageUp,
+    HalfPageUp,
+    PageDown,
+    HalfPageDown,
+}
+
+const ACTION_LIST: [(&str, Action); 35] = [
+    ("repaint", Action::Repaint),
+    ("prev-window", Action::PrevWindow),
+    ("next-window", Action::NextWindow),
+    ("prev-buffer", Action::PrevBuffer),
+    ("next-buffer", Action::NextBuffer),
+    ("window-left", Action::WindowLeft),
+    ("window-down", Action::WindowDown),
+    ("window-up", Action::WindowUp),
+    ("window-right", Action::WindowRight),
+    ("goto-window", Action::GotoWindow),
+    ("grow-window", Action::GrowWindow),
+    ("shrink-window", Action::ShrinkWindow),
+    ("swap-window", Action::SwapWindow),
+    ("zoom-window", Action::ZoomWindow),
+    ("head", Action::Head),
+    ("tail", Action::Tail),
+    ("follow", Action::Follow),
+    ("table", Action::Table),
+    ("delta", Action::Delta),
+    ("up", Action::Up),
+    ("down", Action::Down),
+    ("left", Action::Left),
+    ("right", Action::Right),
+    ("first-column", Action::FirstColumn),
+    ("last-column", Action::LastColumn),
+    ("search-forward", Action::SearchForward),
+    ("search-backward", Action::SearchBackward),
+    ("next-match", Action::NextMatch),
+    ("prev-match", Action::PrevMatch),
+    ("filter", Action::Filter),
+    ("command", Action::Command),
+    ("page-up", Action::PageUp),
+    ("half-page-up", Action::HalfPageUp),
+    ("page-down", Action::PageDown),
+    ("half-page-down", Action::HalfPageDown),
+];
+
+pub(crate) fn string_to_action(s: &str) -> Option<Action> {
+    ACTION_LIST.iter().find(|x| x.0 == s).map(|x| x.1)
+}
+
+// "x", "C-x" or "^x" for CTRL-x, or "UP", "DOWN", "LEFT", "RIGHT"
+pub(crate) fn string_to_key(s: &str) -> Option<u32> {
+    match s {
+        "UP" => return Some(screen::KEY_UP),
+        "DOWN" => return Some(screen::KEY_DOWN),
+        "LEFT" => return Some(screen::KEY_LEFT),
+        "RIGHT" => return Some(screen::KEY_RIGHT),
+        _ => (),
+    }
+    let (t, ctrl) = match s.strip_prefix("C-").or_else(|| s.strip_prefix('^')) {
+        Some(v) if !v.is_empty() => (v, true),
+        _ => (s, false),
+    };
+    let mut c = t.chars();
+    let x = c.next().filter(char::is_ascii_graphic)?;
+    if c.next().is_some() {
+        return None;
+    }
+    if ctrl {
+        Some(screen::key_ctrl(u32::from(x.to_ascii_uppercase())))
+    } else {
+        Some(u32::from(x))
+    }
+}
+
+// prefix keys taking another key, i.e. "g" and CTRL-w
+pub(crate) fn is_prefix(x: u32) -> bool {
+    x == u32::from('g') || x == screen::key_ctrl(u32::from('W'))
+}
+
+// action of key x following prefix key p, e.g. "g_" or CTRL-w h
+pub(crate) fn get_prefixed_action(p: u32, x: u32) -> Option<Action> {
+    if p == u32::from('g') {
+        return (x == u32::from('_')).then_some(Action::LastColumn);
+    }
+    if p != screen::key_ctrl(u32::from('W')) {
+        return None;
+    }
+    let c = char::from_u32(x).unwrap_or_default();
+    let a = if c == 'h' || x == screen::KEY_LEFT || x == screen::key_ctrl(u32::from('H')) {
+        Action::WindowLeft
+    } else if c == 'j' || x == screen::KEY_DOWN || x == screen::key_ctrl(u32::from('J')) {
+        Action::WindowDown
+    } else if c == 'k' || x == screen::KEY_UP || x == screen::key_ctrl(u32::from('K')) {
+        Action::WindowUp
+    } else if c == 'l' || x == screen::KEY_RIGHT || x == screen::key_ctrl(u32::from('L')) {
+        Action::WindowRight
+    } else if c == 'w' || x == screen::key_ctrl(u32::from('W')) {
+        Action::GotoWindow
+    } else if c == 'W' {
+        Action::PrevWindow
+    } else if c == '+' {
+        Action::GrowWindow
+    } else if c == '-' {
+        Action::ShrinkWindow
+    } else if c == 'x' {
+        Action::SwapWindow
+    } else if c == 'o' {
+        Action::ZoomWindow
+    } else {
+        return None;
+    };
+    Some(a)
+}
+
+pub(crate) fn get_default_keymap() -> std::collections::HashMap<u32, Action> {
+    [
+        (screen::key_ctrl(u32::from('L')), Action::Repaint),
+        (u32::from('h'), Action::PrevWindow),
+        (screen::KEY_LEFT, Action::PrevWindow),
+        (u32::from('l'), Action::NextWindow),
+        (screen::KEY_RIGHT, Action::NextWindow),
+        (u32::from('['), Action::PrevBuffer),
+        (u32::from(']'), Action::NextBuffer),
+        (u32::from('+'), Action::GrowWindow),
+        (u32::from('-'), Action::ShrinkWindow),
+        (u32::from('x'), Action::SwapWindow),
+        (u32::from('z'), Action::ZoomWindow),
+        (u32::from('0'), Action::Head),
+        (u32::from('$'), Action::Tail),
+        (u32::from('F'), Action::Follow),
+        (u32::from('T'), Action::Table),
+        (u32::from('D'), Action::Delta),
+        (u32::from('k'), Action::Up),
+        (screen::KEY_UP, Action::Up),
+        (u32::from('j'), Action::Down),
+        (screen::KEY_DOWN, Action::Down),
+        (u32::from('H'), Action::Left),
+        (u32::from('L'), Action::Right),
+        (u32::from('^'), Action::FirstColumn),
+        (u32::from('/'), Action::SearchForward),
+        (u32::from('?'), Action::SearchBackward),
+        (u32::from('n'), Action::NextMatch),
+        (u32::from('N'), Action::PrevMatch),
+        (u32::from('&'), Action::Filter),
+        (u32::from(':'), Action::Command),
+        (screen::key_ctrl(u32::from('B')), Action::PageUp),
+        (screen::key_ctrl(u32::from('U')), Action::HalfPageUp),
+        (screen::key_ctrl(u32::from('F')), Action::PageDown),
+        (screen::key_ctrl(u32::from('D')), Action::HalfPageDown),
+    ]
+    .into_iter()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_string_to_key() {
+        assert_eq!(super::string_to_key("j"), Some(u32::from('j')));
+        assert_eq!(super::string_to_key("^"), Some(u32::from('^')));
+        assert_eq!(super::string_to_key("C-b"), Some(0x02));
+        assert_eq!(super::string_to_key("^B"), Some(0x02));
+        assert_eq!(super::string_to_key("UP"), Some(super::screen::KEY_UP));
+        assert_eq!(super::string_to_key(""), None);
+        assert_eq!(super::string_to_key("jj"), None);
+        assert_eq!(super::string_to_key(" "), None);
+    }
+}
@@ -0,0 +1,570 @@
+use crate::Result;
+
+// Layout tree, where leaves are windows in order of allocation.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Layout {
+    Window,
+    Split(bool, Vec<(f64, Layout)>), // side by side if true, with weights
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Weight {
+    None,
+    Relative(usize),
+    Percent(usize),
+}
+
+impl Layout {
+    // Change share of window seq within its parent split by d, e.g. 0.1 to
+    // make it 10% of the parent larger.
+    pub(crate) fn resize_window(&mut self, seq: usize, d: f64) -> bool {
+        let mut path = vec![];
+        if !self.get_path(seq, &mut 0, &mut path) {
+            return false;
+        }
+        let Some((&k, path)) = path.split_last() else {
+            return false;
+        };
+        let mut l = self;
+        for &i in path {
+            l = match l {
+                Layout::Split(_, v) => &mut v[i].1,
+                Layout::Window => return false,
+            };
+        }
+        let Layout::Split(_, v) = l else {
+            return false;
+        };
+        if v.len() < 2 {
+            return false;
+        }
+        let total: f64 = v.iter().map(|x| x.0).sum();
+        let w = v[k].0;
+        let s = (w / total + d).clamp(0.05, 0.95);
+        v[k].0 = s * (total - w) / (1.0 - s);
+        true
+    }
+
+    // Append a window to the last column, stacked below its last window.
+    pub(crate) fn add_window(&mut self) {
+        match self {
+            Layout::Split(d, v) => match v.last_mut() {
+                // column with a single window, split it rather than adding
+                // a column
+                Some((_, x @ Layout::Window)) => {
+                    *x = Layout::Split(!*d, vec![(1.0, Layout::Window), (1.0, Layout::Window)]);
+                }
+                Some((_, x)) => x.append_window(),
+                None => v.push((1.0, Layout::Window)),
+            },
+            Layout::Window => self.append_window(),
+        }
+    }
+
+    // Append a window next to the last window, with average weight of its
+    // siblings.
+    fn append_window(&mut self) {
+        match self {
+            Layout::Window => {
+                *self = Layout::Split(false, vec![(1.0, Layout::Window), (1.0, Layout::Window)]);
+            }
+            Layout::Split(_, v) => match v.last_mut() {
+                Some((_, x @ Layout::Split(..))) => x.append_window(),
+                _ => {
+                    let w = v.iter().map(|x| x.0).sum::<f64>() / v.len().max(1) as f64;
+                    v.push((if w > 0.0 { w } else { 1.0 }, Layout::Window));
+                }
+            },
+        }
+    }
+
+    // child indices from root to window seq
+    fn get_path(&self, seq: usize, n: &mut usize, path: &mut Vec<usize>) -> bool {
+        match self {
+            Layout::Window => {
+                if *n == seq {
+                    return true;
+                }
+                *n += 1;
+            }
+            Layout::Split(_, l) => {
+                for (i, x) in l.iter().enumerate() {
+                    path.push(i);
+                    if x.1.get_path(seq, n, path) {
+                        return true;
+                    }
+                    path.pop();
+                }
+            }
+        }
+        false
+    }
+
+    // true if top level split is not side by side, see parse()
+    pub(crate) fn is_rotated(&self) -> bool {
+        matches!(self, Layout::Split(false, _))
+    }
+
+    // (ylen, xlen, ypos, xpos) of each window
+    pub(crate) fn get_geometry(
+        &self,
+        ylen: usize,
+        xlen: usize,
+    ) -> Result<Vec<(usize, usize, usize, usize)>> {
+        let mut v = vec![];
+        self.collect_geometry(ylen, xlen, 0, 0, &mut v)?;
+        Ok(v)
+    }
+
+    fn collect_geometry(
+        &self,
+        ylen: usize,
+        xlen: usize,
+        ypos: usize,
+        xpos: usize,
+        v: &mut Vec<(usize, usize, usize, usize)>,
+    ) -> Result<()> {
+        match self {
+            Layout::Window => {
+                if ylen < 2 || xlen < 2 {
+                    return Err(crate::invalid_input("terminal too small for layout"));
+                }
+                v.push((ylen, xlen, ypos, xpos));
+            }
+            Layout::Split(true, l) => {
+                let mut pos = xpos;
+                for (x, n) in l.iter().zip(split_length(xlen, l)) {
+                    x.1.collect_geometry(ylen, n, ypos, pos, v)?;
+                    pos += n;
+                }
+            }
+            Layout::Split(false, l) => {
+                let mut pos = ypos;
+                for (x, n) in l.iter().zip(split_length(ylen, l)) {
+                    x.1.collect_geometry(n, xlen, pos, xpos, v)?;
+                    pos += n;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Layout string in grammar accepted by parse(), with weights as is if whole
+// numbers, otherwise in percentage of their siblings.
+impl std::fmt::Display for Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Layout::Window => write!(f, "1"),
+            Layout::Split(h, v) => write!(f, "{}", format_split(*h, v)),
+        }
+    }
+}
+
+fn format_split(horizontal: bool, v: &[(f64, Layout)]) -> String {
+    let total: f64 = v.iter().map(|x| x.0).sum();
+    let weighted = v.iter().any(|x| x.0 != v[0].0);
+    let whole = v.iter().all(|x| x.0.fract() == 0.0);
+    let mut l = vec![];
+    for (w, x) in v {
+        let body = match x {
+            Layout::Window => "1".to_string(),
+            Layout::Split(h, c) if *h != horizontal => {
+                if c.iter().all(|y| y.0 == c[0].0 && y.1 == Layout::Window) {
+                    c.len().to_string()
+                } else {
+                    format!("({})", format_split(*h, c))
+                }
+            }
+            // nest once more to get back to the same direction
+            Layout::Split(h, c) => format!("(({}))", format_split(*h, c)),
+        };
+        if weighted && whole {
+            l.push(format!("{w}:{body}"));
+        } else if weighted {
+            let n = (100.0 * w / total).round().max(1.0);
+            l.push(format!("{n}:{body}"));
+        } else if body.len() > 1 && !body.starts_with('(') {
+            // more than 9 windows need a weight, which is equal to others
+            l.push(format!("1:{body}"));
+        } else {
+            l.push(body);
+        }
+    }
+    l.join(",")
+}
+
+// Split len by weights, rounding at cumulative offsets so that sum is len.
+fn split_length(len: usize, l: &[(f64, Layout)]) -> Vec<usize> {
+    let total: f64 = l.iter().map(|x| x.0).sum();
+    let mut v = vec![];
+    let mut prev = 0;
+    let mut cum = 0.0;
+    for (i, x) in l.iter().enumerate() {
+        cum += x.0;
+        let next = if i == l.len() - 1 {
+            len
+        } else {
+            (len as f64 * cum / total) as usize
+        };
+        v.push(next - prev);
+        prev = next;
+    }
+    v
+}
+
+fn window_list(n: usize, horizontal: bool) -> Layout {
+    if n == 1 {
+        Layout::Window
+    } else {
+        Layout::Split(horizontal, vec![(1.0, Layout::Window); n])
+    }
+}
+
+// Legacy layout, i.e. each of "1"-"9" and "a"-"f" is number of windows in
+// a column. Bare number of windows is thus a single digit elsewhere too, and
+// more windows need a weight, e.g. "1:10" instead of "10".
+fn is_legacy(s: &str) -> bool {
+    s.chars()
+        .all(|c| ('1'..='9').contains(&c) || ('a'..='f').contains(&c))
+}
+
+// Parse layout string, where columns are side by side unless rotated.
+pub(crate) fn parse(s: &str, rotate: bool) -> Result<Layout> {
+    if s.is_empty() {
+        return Err(crate::invalid_input("empty layout"));
+    }
+    if is_legacy(s) {
+        let mut v = vec![];
+        for c in s.chars() {
+            let n = c.to_digit(16).ok_or_else(crate::util::error)?;
+            v.push((1.0, window_list(usize::try_from(n)?, rotate)));
+        }
+        return Ok(Layout::Split(!rotate, v));
+    }
+    let mut p = Parser {
+        s,
+        pos: 0,
+        ambiguous: None,
+    };
+    let l = p.parse_split(!rotate)?;
+    if p.pos < s.len() {
+        return Err(p.error(p.pos, "unexpected character"));
+    }
+    // reported after syntax errors, which may be typo of legacy layout
+    if let Some((pos, n)) = p.ambiguous {
+        let msg = format!("ambiguous number of windows, use \"1:{n}\" for {n} windows");
+        return Err(p.error(pos, &msg));
+    }
+    Ok(l)
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+    ambiguous: Option<(usize, usize)>, // the first bare number of more than 9 windows
+}
+
+impl Parser<'_> {
+    fn error(&self, pos: usize, msg: &str) -> Box<dyn std::error::Error> {
+        crate::invalid_input(&format!(
+            "invalid layout: {msg}\n  {}\n  {}^",
+            self.s,
+            " ".repeat(self.s[..pos].chars().count())
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn parse_number(&mut self) -> Result<Option<usize>> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Ok(None);
+        }
+        match self.s[start..self.pos].parse() {
+            Ok(v) => Ok(Some(v)),
+            Err(_) => Err(self.error(start, "number too large")),
+        }
+    }
+
+    // <item>[,<item>...] split in given direction
+    fn parse_split(&mut self, horizontal: bool) -> Result<Layout> {
+        let mut v = vec![];
+        loop {
+            let start = self.pos;
+            let (w, l) = self.parse_item(horizontal)?;
+            v.push((start, w, l));
+            if self.peek() == Some(',') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        // weights default to 1, or evenly share remaining percentage
+        let pct = v.iter().find(|x| matches!(x.1, Weight::Percent(_)));
+        let rel = v.iter().find(|x| matches!(x.1, Weight::Relative(_)));
+        let default = match (pct, rel) {
+            (Some(_), Some(x)) => {
+                return Err(self.error(x.0, "mixed percentage and relative weights"));
+            }
+            (Some(_), None) => {
+                let mut sum = 0;
+                for x in &v {
+                    if let Weight::Percent(n) = x.1 {
+                        sum += n;
+                        if sum > 100 {
+                            return Err(self.error(x.0, "percentages exceed 100"));
+                        }
+                    }
+                }
+                let n = v.iter().filter(|x| matches!(x.1, Weight::None)).count();
+                if n > 0 {
+                    (100 - sum) as f64 / n as f64
+                } else {
+                    0.0
+                }
+            }
+            _ => 1.0,
+        };
+        let mut l = vec![];
+        for (start, w, x) in v {
+            let w = match w {
+                Weight::None => default,
+                Weight::Relative(n) | Weight::Percent(n) => n as f64,
+            };
+            if w <= 0.0 {
+                return Err(self.error(start, "no room for window"));
+            }
+            l.push((w, x));
+        }
+        Ok(Layout::Split(horizontal, l))
+    }
+
+    // [<weight>[%]:]<n> or [<weight>[%]:](<split>)
+    fn parse_item(&mut self, horizontal: bool) -> Result<(Weight, Layout)> {
+        let mut body = self.pos;
+        let mut n = self.parse_number()?;
+        let mut w = Weight::None;
+        if n.is_some() && self.peek() == Some('%') {
+            self.pos += 1;
+            if self.peek() != Some(':') {
+                return Err(self.error(self.pos, "expected ':'"));
+            }
+            w = Weight::Percent(n.unwrap_or_default());
+        } else if n.is_some() && self.peek() == Some(':') {
+            w = Weight::Relative(n.unwrap_or_default());
+        }
+        if !matches!(w, Weight::None) {
+            self.pos += 1;
+            body = self.pos;
+            n = None;
+        }
+        if n.is_none() && self.peek() == Some('(') {
+            self.pos += 1;
+            let l = self.parse_split(!horizontal)?;
+            if self.peek() != Some(')') {
+                return Err(self.error(self.pos, "expected ')'"));
+            }
+            self.pos += 1;
+            return Ok((w, l));
+        }
+        if n.is_none() {
+            n = self.parse_number()?;
+        }
+        if let Some(n) = n.filter(|&n| n > 9 && matches!(w, Weight::None)) {
+            self.ambiguous.get_or_insert((body, n));
+        }
+        match n {
+            Some(0) => Err(self.error(body, "number of windows must be positive")),
+            Some(n) => Ok((w, window_list(n, !horizontal))),
+            None if self.peek().is_none() => {
+                Err(self.error(self.pos, "expected number of windows"))
+            }
+            None => Err(self.error(self.pos, "expected number of windows or '('")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Layout;
+
+    #[test]
+    fn test_parse() {
+        let l = super::parse("12", false).unwrap();
+        assert_eq!(
+            l,
+            Layout::Split(
+                true,
+                vec![(1.0, Layout::Window), (1.0, super::window_list(2, false))]
+            )
+        );
+        assert_eq!(
+            super::parse("1f", false)
+                .unwrap()
+                .get_geometry(100, 100)
+                .unwrap()
+                .len(),
+            16
+        );
+        assert_eq!(
+            super::parse("1:20", false)
+                .unwrap()
+                .get_geometry(100, 100)
+                .unwrap()
+                .len(),
+            20
+        );
+        assert_eq!(
+            super::parse("1:16", false)
+                .unwrap()
+                .get_geometry(100, 100)
+                .unwrap()
+                .len(),
+            16
+        );
+        assert_eq!(
+            super::parse("2,(1,2)", false)
+                .unwrap()
+                .get_geometry(100, 100)
+                .unwrap()
+                .len(),
+            5
+        );
+
+        let l = super::parse("70%:1,(1,2:3)", true).unwrap();
+        let Layout::Split(false, v) = &l else {
+            panic!("{l:?}");
+        };
+        assert_eq!(v[0].0, 70.0);
+        assert_eq!(v[1].0, 30.0);
+        let Layout::Split(true, v) = &v[1].1 else {
+            panic!("{l:?}");
+        };
+        assert_eq!(v[1].0, 2.0);
+        assert_eq!(v[1].1, super::window_list(3, false));
+
+        for (s, pos) in [
+            ("", None),
+            ("10", Some(0)),
+            ("20", Some(0)),
+            ("1,20", Some(2)),
+            ("2:1,(1,12)", Some(7)),
+            ("0", Some(0)),
+            ("1,", Some(2)),
+            ("1,x", Some(2)),
+            ("12x", Some(2)),
+            ("1,(2", Some(4)),
+            ("1,0", Some(2)),
+            ("1,2:0", Some(4)),
+            ("1)", Some(1)),
+            ("60%:1,50%:1", Some(6)),
+            ("60%:1,2:1", Some(6)),
+            ("50%1", Some(3)),
+        ] {
+            let e = super::parse(s, false).unwrap_err().to_string();
+            if let Some(pos) = pos {
+                let caret = e.lines().last().unwrap();
+                assert_eq!(caret.len() - 3, pos, "{s}: {e}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_geometry() {
+        let l = super::parse("123", false).unwrap();
+        assert_eq!(
+            l.get_geometry(10, 30).unwrap(),
+            vec![
+                (10, 10, 0, 0),
+                (5, 10, 0, 10),
+                (5, 10, 5, 10),
+                (3, 10, 0, 20),
+                (3, 10, 3, 20),
+                (4, 10, 6, 20),
+            ]
+        );
+        let l = super::parse("3:1,1:(1,1)", false).unwrap();
+        assert_eq!(
+            l.get_geometry(10, 40).unwrap(),
+            vec![(10, 30, 0, 0), (5, 10, 0, 30), (5, 10, 5, 30)]
+        );
+        let l = super::parse("12", true).unwrap();
+        assert_eq!(
+            l.get_geometry(10, 40).unwrap(),
+            vec![(5, 40, 0, 0), (5, 20, 5, 0), (5, 20, 5, 20)]
+        );
+        assert!(super::parse("1:20", false)
+            .unwrap()
+            .get_geometry(10, 40)
+            .is_err());
+    }
+
+    #[test]
+    fn test_resize_window() {
+        let mut l = super::parse("12", false).unwrap();
+        assert!(l.resize_window(2, 0.25));
+        assert_eq!(
+            l.get_geometry(20, 40).unwrap(),
+            vec![(20, 20, 0, 0), (5, 20, 0, 20), (15, 20, 5, 20)]
+        );
+        assert!(l.resize_window(0, -0.25));
+        assert_eq!(l.get_geometry(20, 40).unwrap()[0], (20, 10, 0, 0));
+        assert!(!l.resize_window(3, 0.1));
+        assert!(!super::parse("1", false).unwrap().resize_window(0, 0.1));
+    }
+
+    #[test]
+    fn test_add_window() {
+        let mut l = super::parse("12", false).unwrap();
+        l.add_window();
+        assert_eq!(l, super::parse("13", false).unwrap());
+        let mut l = super::parse("1", false).unwrap();
+        for t in ["2", "3"] {
+            l.add_window();
+            assert_eq!(l, super::parse(t, false).unwrap());
+        }
+        let mut l = super::parse("11", true).unwrap();
+        l.add_window();
+        assert_eq!(l, super::parse("12", true).unwrap());
+        let mut l = super::parse("1,2:(1,3)", false).unwrap();
+        l.add_window();
+        assert_eq!(l.to_string(), "1:1,2:(1,4)");
+        let mut l = super::Layout::Window;
+        l.add_window();
+        assert_eq!(l.get_geometry(20, 40).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_to_string() {
+        for (s, rotate, t) in [
+            ("12", false, "1,2"),
+            ("3", true, "3"),
+            ("1:12", false, "1:12"),
+            ("1:10", true, "1:10"),
+            ("1,1:20", false, "1,1:20"),
+            ("2:1,1:(1,1:12)", false, "2:1,1:(1,1:12)"),
+            ("70%:1,(1,2:3)", false, "70:1,30:(1:1,2:3)"),
+            ("2:1,1:(1,1)", false, "2:1,1:2"),
+        ] {
+            let l = super::parse(s, rotate).unwrap();
+            assert_eq!(l.to_string(), t, "{s}");
+            assert_eq!(l.is_rotated(), rotate, "{s}");
+            let m = super::parse(t, l.is_rotated()).unwrap();
+            assert_eq!(
+                l.get_geometry(100, 100).unwrap(),
+                m.get_geometry(100, 100).unwrap(),
+                "{s}"
+            );
+        }
+        let mut l = super::parse("12", false).unwrap();
+        l.resize_window(0, 0.1);
+        assert_eq!(l.to_string(), "60:1,40:2");
+    }
+}
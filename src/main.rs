@@ -2,9 +2,17 @@
 extern crate lazy_static;
 
 mod buffer;
+mod command;
+mod config;
 mod container;
+mod delta;
 mod frame;
+mod keymap;
+mod layout;
 mod panel;
+mod process;
+mod session;
+mod table;
 mod util;
 mod window;
 
@@ -28,7 +36,7 @@ type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 #[derive(Debug)]
 struct Opt {
-    layout: Vec<usize>,
+    layout: layout::Layout,
     sinterval: u64,
     minterval: u64,
     fgcolor: i16,
@@ -37,6 +45,18 @@ struct Opt {
     foldline: bool,
     rotatecol: bool,
     blinkline: bool,
+    follow: bool,
+    table: bool,
+    delta: delta::Mode,
+    tabstop: usize,
+    ring: usize,
+    include: Vec<(String, regex::Regex)>,
+    exclude: Vec<(String, regex::Regex)>,
+    pathopt: Vec<(String, String)>, // path and window options
+    keymap: Vec<(u32, keymap::Action)>,
+    session: Option<String>,
+    pid: Option<u32>,
+    pgrep: Option<String>, // process name to follow across restarts
     usedelay: bool,
     debug: bool,
 }
@@ -44,7 +64,7 @@ struct Opt {
 impl Default for Opt {
     fn default() -> Self {
         Self {
-            layout: Vec::new(),
+            layout: layout::Layout::Window,
             sinterval: 1,
             minterval: 0,
             fgcolor: -1,
@@ -53,12 +73,70 @@ impl Default for Opt {
             foldline: false,
             rotatecol: false,
             blinkline: true,
+            follow: false,
+            table: false,
+            delta: delta::Mode::Raw,
+            tabstop: 8,
+            ring: 10000,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            pathopt: Vec::new(),
+            keymap: Vec::new(),
+            session: None,
+            pid: None,
+            pgrep: None,
             usedelay: false,
             debug: false,
         }
     }
 }
 
+impl Opt {
+    fn get_window_opt(&self, f: &str) -> WindowOpt {
+        WindowOpt {
+            interval: self.sinterval * 1000 + self.minterval,
+            showlnum: self.showlnum,
+            foldline: self.foldline,
+            blinkline: self.blinkline,
+            follow: self.follow,
+            table: self.table,
+            delta: self.delta,
+            tabstop: self.tabstop,
+            timeout: 0,
+            ring: self.ring,
+            include: self.include.iter().rfind(|x| x.0 == f).map(|x| x.1.clone()),
+            exclude: self.exclude.iter().rfind(|x| x.0 == f).map(|x| x.1.clone()),
+        }
+    }
+}
+
+// per window options, taken from Opt unless specified for path
+#[derive(Clone, Debug)]
+struct WindowOpt {
+    interval: u64, // milli second
+    showlnum: bool,
+    foldline: bool,
+    blinkline: bool,
+    follow: bool,
+    table: bool,
+    delta: delta::Mode,
+    tabstop: usize,
+    timeout: u64, // milli second, 0 for interval
+    ring: usize,
+    include: Option<regex::Regex>,
+    exclude: Option<regex::Regex>,
+}
+
+impl WindowOpt {
+    fn get_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(if self.timeout == 0 {
+            self.interval
+        } else {
+            self.timeout
+        })
+    }
+}
+
 fn get_version_string() -> String {
     format!("{}.{}.{}", VERSION[0], VERSION[1], VERSION[2])
 }
@@ -72,14 +150,83 @@ fn usage(progname: &str, opts: &getopts::Options) {
         "{}",
         opts.usage(&format!("usage: {progname} [<options>] <paths>"))
     );
+    println!(
+        "Paths:
+  Each path can be followed by \":<options>\" to override above options
+  for the window, where <options> is a comma separated list of
+  interval=<n>[ms|s], timeout=<n>[ms|s], tabstop=<n>, ring=<n>, [no]lnum,
+  [no]fold, [no]blink, [no]follow, [no]table, raw, delta, rate.
+  e.g. \"/proc/meminfo:interval=200ms,lnum\"
+  Path \"-\" reads stdin, which as well as FIFO keeps the last --ring lines.
+  Directory or quoted glob, e.g. \"/var/log/app/*.log\", opens each matching
+  file, including ones created later, in a window stacked in the last column
+  while they fit.
+  Path \"!<command>\" runs <command> by shell every interval and shows its
+  output, killed after timeout=<n>[ms|s] option or interval. Append \":\"
+  if <command> contains \":\". e.g. \"!ss -s:timeout=2s\"
+  Paths beyond the number of windows are added to buffer lists of windows
+  in turn.
+
+Layout:
+  Layout is either a string of \"1\"-\"9\" and \"a\"-\"f\" each being number of
+  windows in a column, or a comma separated list of columns, each being
+  [<weight>:]<n> for <n> windows, or [<weight>:](<list>) for a list which
+  splits the column into rows in the same way, alternating direction with
+  each nesting level. <weight> is relative, or a percentage with \"%\".
+  <n> above 9 needs a weight, as a bare number is always taken as the
+  former, e.g. \"16\" is a column with a window and a column with 6 windows,
+  while \"1:16\" is a column with 16 windows.
+  e.g. \"2:1,1:3\" for a wide column and a column with 3 windows, \"1:20,(1,2)\"
+  for a column with 20 windows and a column with a window above 2 windows.
+
+Config:
+  Defaults for above options, named layouts, key bindings and per path
+  settings are read from .{}.toml under ${PROCSTAT_HOME} or home
+  directory, or from a file given by --config. Command line options take
+  precedence over the config file.
+",
+        util::get_basename(progname).unwrap_or_default()
+    );
     println!(
         "Commands:
   0 - Set current position to the first line of the buffer
   $ - Set current position to the last line of the buffer
-  k|UP - Scroll upward
-  j|DOWN - Scroll downward
-  h|LEFT - Select next window
-  l|RIGHT - Select previous window
+  F - Toggle follow mode to keep the last line of the buffer visible
+  D - Cycle numbers as is, difference from previous refresh, difference per
+      second
+  T - Toggle table mode to align keys and numbers with kB in MiB, GiB, etc
+  [N]k|UP - Scroll N lines upward
+  [N]j|DOWN - Scroll N lines downward
+  h|LEFT - Select previous window
+  l|RIGHT - Select next window
+  CTRL-w h|j|k|l - Select window left, below, above or right of current window
+  [N]CTRL-w w - Select window N, or next window without N
+  CTRL-w W - Select previous window
+  CTRL-w +|-|x|o - Same as +, -, x, z below
+  [ - Select previous buffer of current window
+  ] - Select next buffer of current window
+  + - Grow current window within its column
+  - - Shrink current window within its column
+  x - Swap buffers of current window with next window
+  z - Toggle zoom of current window to full screen
+  H - Scroll half page leftward
+  L - Scroll half page rightward
+  ^ - Set current position to the first column
+  g_ - Set current position to the last column of the longest line
+  /PATTERN - Search forward for regex PATTERN
+  ?PATTERN - Search backward for regex PATTERN
+  n - Repeat previous search
+  N - Repeat previous search in opposite direction
+  &PATTERN - Only show lines matching regex PATTERN, \"&!PATTERN\" to hide
+             matching lines, \"&\" to clear filter
+  :e PATH - Open PATH in current window if empty, otherwise in an empty
+            window if any, otherwise add to buffers of current window
+  :bn - Select next buffer of current window
+  :bp - Select previous buffer of current window
+  :bd - Close buffer of current window
+  :mks [PATH] - Save layout and windows to PATH, or session file given by
+               --session without PATH
+  :q - Quit
   CTRL-b - Scroll one page upward
   CTRL-u - Scroll half page upward
   CTRL-f - Scroll one page downward
@@ -88,20 +235,117 @@ fn usage(progname: &str, opts: &getopts::Options) {
     );
 }
 
-fn init_file_logger(progname: &str) -> Result<()> {
+fn invalid_input(s: &str) -> Box<dyn std::error::Error> {
+    Box::new(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        s.to_string(),
+    ))
+}
+
+// "path:key[=value],..." to path and window options,
+// unless path with ":" exists as is
+fn parse_path(arg: &str, opt: &Opt) -> Result<(String, WindowOpt)> {
+    let (f, s) = match arg.rsplit_once(':') {
+        Some((f, s)) if !std::path::Path::new(arg).exists() => (f, s),
+        _ => (arg, ""),
+    };
+    let mut wopt = get_path_opt(f, opt)?;
+    parse_window_opt(s, &mut wopt)?;
+    Ok((f.to_string(), wopt))
+}
+
+// window options for path, i.e. those from config file for path over Opt
+fn get_path_opt(f: &str, opt: &Opt) -> Result<WindowOpt> {
+    let abspath = util::get_abspath(f)?;
+    let mut wopt = opt.get_window_opt(&abspath);
+    for x in opt.pathopt.iter().filter(|x| x.0 == abspath) {
+        parse_window_opt(&x.1, &mut wopt)?;
+    }
+    Ok(wopt)
+}
+
+// "key[=value],..." to window options
+fn parse_window_opt(s: &str, wopt: &mut WindowOpt) -> Result<()> {
+    for x in s.split(',').filter(|x| !x.is_empty()) {
+        match x.split_once('=') {
+            Some(("interval", v)) => wopt.interval = parse_interval(v)?,
+            Some(("timeout", v)) => wopt.timeout = parse_interval(v)?,
+            Some(("ring", v)) => match v.parse() {
+                Ok(v) => wopt.ring = v,
+                Err(e) => return Err(invalid_input(&format!("{x}: {e}"))),
+            },
+            Some(("tabstop", v)) => match v.parse() {
+                Ok(v) => wopt.tabstop = v,
+                Err(e) => return Err(invalid_input(&format!("{x}: {e}"))),
+            },
+            Some(_) => return Err(invalid_input(&format!("invalid option {x}"))),
+            None => match x {
+                "lnum" => wopt.showlnum = true,
+                "nolnum" => wopt.showlnum = false,
+                "fold" => wopt.foldline = true,
+                "nofold" => wopt.foldline = false,
+                "blink" => wopt.blinkline = true,
+                "noblink" => wopt.blinkline = false,
+                "follow" => wopt.follow = true,
+                "nofollow" => wopt.follow = false,
+                "table" => wopt.table = true,
+                "notable" => wopt.table = false,
+                "raw" => wopt.delta = delta::Mode::Raw,
+                "delta" => wopt.delta = delta::Mode::Delta,
+                "rate" => wopt.delta = delta::Mode::Rate,
+                _ => return Err(invalid_input(&format!("invalid option {x}"))),
+            },
+        }
+    }
+    Ok(())
+}
+
+// "200ms", "2s" or "2" (second) to milli second
+fn parse_interval(s: &str) -> Result<u64> {
+    let (v, n) = if let Some(v) = s.strip_suffix("ms") {
+        (v, 1)
+    } else if let Some(v) = s.strip_suffix('s') {
+        (v, 1000)
+    } else {
+        (s, 1000)
+    };
+    match v.parse::<u64>() {
+        Ok(v) => Ok(v * n),
+        Err(e) => Err(invalid_input(&format!("{s}: {e}"))),
+    }
+}
+
+// "path=regex" to absolute path and regex
+fn parse_filter(args: &[String]) -> Result<Vec<(String, regex::Regex)>> {
+    let mut v = vec![];
+    for s in args {
+        let Some((f, re)) = s.split_once('=') else {
+            return Err(invalid_input(&format!("invalid filter {s}")));
+        };
+        v.push((util::get_abspath(f)?, regex::Regex::new(re)?));
+    }
+    Ok(v)
+}
+
+// directory for config and log files
+fn get_procstat_home() -> String {
     let home = util::get_home_path();
-    let name = format!(".{}.log", util::get_basename(progname)?);
-    let f = match std::env::var(PROCSTAT_HOME) {
+    match std::env::var(PROCSTAT_HOME) {
         Ok(v) => {
             if util::is_dir(&v) {
-                util::join_path(&v, &name)
+                v
             } else {
                 println!("{PROCSTAT_HOME} not a directory, using {home} instead");
-                util::join_path(&home, &name)
+                home
             }
         }
-        Err(_) => util::join_path(&home, &name),
-    };
+        Err(_) => home,
+    }
+}
+
+fn init_file_logger(home: &str, progname: &str) -> Result<()> {
+    let name = format!(".{}.log", util::get_basename(progname)?);
+    let f = util::join_path(home, &name);
     Ok(simplelog::CombinedLogger::init(vec![
         simplelog::WriteLogger::new(
             simplelog::LevelFilter::Trace,
@@ -138,7 +382,9 @@ fn main() {
         "c",
         "",
         "Set column layout. \
-            e.g. \"-c 123\" to make 3 columns with 1,2,3 windows for each",
+            e.g. \"-c 123\" to make 3 columns with 1,2,3 windows for each. \
+            See below for details. \
+            Also takes name of layout defined in config file",
         "STRING",
     );
     opts.optopt(
@@ -169,14 +415,79 @@ fn main() {
             e.g. \"-t 500 -m\" to refresh screen every 500 milli seconds",
     );
     opts.optflag("n", "", "Show line number");
+    opts.optopt(
+        "",
+        "tabstop",
+        "Set tab width. Default is 8. \
+            \"--tabstop 0\" to show tab as \"^I\"",
+        "STRING",
+    );
+    opts.optopt(
+        "",
+        "ring",
+        "Set maximum number of lines kept for stdin and FIFO. Default is 10000",
+        "STRING",
+    );
+    opts.optmulti(
+        "",
+        "include",
+        "Only show lines matching regex in path. \
+            e.g. \"--include /var/log/syslog=error\"",
+        "STRING",
+    );
+    opts.optmulti(
+        "",
+        "exclude",
+        "Hide lines matching regex in path. \
+            e.g. \"--exclude /proc/interrupts=' 0 +0 '\"",
+        "STRING",
+    );
     opts.optflag("f", "", "Fold lines when longer than window width");
     opts.optflag("r", "", "Rotate column layout");
     opts.optflag("", "noblink", "Disable blink");
+    opts.optflag("", "follow", "Start windows in follow mode");
+    opts.optflag(
+        "",
+        "table",
+        "Show key value lines, e.g. of /proc/meminfo, as aligned table",
+    );
+    opts.optflag(
+        "",
+        "delta",
+        "Show numbers as difference from previous refresh",
+    );
+    opts.optflag("", "rate", "Show numbers as difference per second");
     opts.optflag(
         "",
         "usedelay",
         "Add random delay time before each window starts",
     );
+    opts.optopt(
+        "",
+        "config",
+        "Read config file instead of the default one",
+        "STRING",
+    );
+    opts.optopt(
+        "",
+        "session",
+        "Restore layout and windows from session file if exists. \
+            \":mksession\" saves to the file",
+        "STRING",
+    );
+    opts.optopt(
+        "",
+        "pid",
+        "Open status, io, sched, limits and smaps_rollup under /proc/<pid>",
+        "STRING",
+    );
+    opts.optopt(
+        "",
+        "pgrep",
+        "Same as --pid for the oldest process of name, and follow process \
+            of the name once exited",
+        "STRING",
+    );
     opts.optflag("", "debug", "Enable debug log");
     opts.optflag("v", "version", "Print version and exit");
     opts.optflag("h", "help", "print this help menu");
@@ -198,92 +509,216 @@ fn main() {
         std::process::exit(1);
     }
 
+    let home = get_procstat_home();
+    let (cfgpath, required) = match matches.opt_str("config") {
+        Some(v) => (v, true),
+        None => match config::get_path(&home, progname) {
+            Ok(v) => (v, false),
+            Err(e) => {
+                println!("{e}");
+                std::process::exit(1);
+            }
+        },
+    };
+    let cfg = match config::load(&cfgpath, required) {
+        Ok(v) => v,
+        Err(e) => {
+            println!("{cfgpath}: {e}");
+            std::process::exit(1);
+        }
+    };
+
     let mut opt = Opt {
         ..Default::default()
     };
-    let mut layout = match matches.opt_str("c") {
-        Some(v) => v.to_lowercase(),
-        None => String::new(),
-    };
-    opt.fgcolor = match matches.opt_str("fg") {
-        Some(v) => screen::string_to_color(&v),
-        None => -1,
+    if let Err(e) = cfg.apply(&mut opt) {
+        println!("{cfgpath}: {e}");
+        std::process::exit(1);
+    }
+    let session = match matches.opt_str("session") {
+        Some(f) => match session::load(&f) {
+            Ok(v) => {
+                opt.session = Some(f);
+                v
+            }
+            Err(e) => {
+                println!("{f}: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => None,
     };
-    opt.bgcolor = match matches.opt_str("bg") {
-        Some(v) => screen::string_to_color(&v),
-        None => -1,
+    let mut layout = match matches.opt_str("c").as_deref().or(cfg.get_layout()) {
+        Some(v) => cfg.resolve_layout(v).to_lowercase(),
+        None => String::new(),
     };
-    opt.sinterval = match matches.opt_str("t") {
-        Some(v) => match v.parse::<u64>() {
+    if let Some(v) = matches.opt_str("fg") {
+        opt.fgcolor = screen::string_to_color(&v);
+    }
+    if let Some(v) = matches.opt_str("bg") {
+        opt.bgcolor = screen::string_to_color(&v);
+    }
+    if let Some(v) = matches.opt_str("t") {
+        let x = match v.parse::<u64>() {
             Ok(v) => v,
             Err(e) => {
                 println!("{v}: {e}");
                 std::process::exit(1);
             }
-        },
-        None => 1,
-    };
-    if matches.opt_present("m") {
-        let x = opt.sinterval;
-        opt.sinterval = x / 1000;
-        opt.minterval = x % 1000;
-    }
-    opt.showlnum = matches.opt_present("n");
-    opt.foldline = matches.opt_present("f");
-    opt.blinkline = !matches.opt_present("noblink");
-    opt.rotatecol = matches.opt_present("r");
-    opt.usedelay = matches.opt_present("usedelay");
-    opt.debug = matches.opt_present("debug");
-
-    let args = matches.free;
+        };
+        if matches.opt_present("m") {
+            opt.sinterval = x / 1000;
+            opt.minterval = x % 1000;
+        } else {
+            opt.sinterval = x;
+            opt.minterval = 0;
+        }
+    }
+    if let Some(v) = matches.opt_str("tabstop") {
+        opt.tabstop = match v.parse::<usize>() {
+            Ok(v) => v,
+            Err(e) => {
+                println!("{v}: {e}");
+                std::process::exit(1);
+            }
+        };
+    }
+    if let Some(v) = matches.opt_str("ring") {
+        opt.ring = match v.parse::<usize>() {
+            Ok(v) => v,
+            Err(e) => {
+                println!("{v}: {e}");
+                std::process::exit(1);
+            }
+        };
+    }
+    // appended to those from config file, the last one for path is used
+    match parse_filter(&matches.opt_strs("include")) {
+        Ok(v) => opt.include.extend(v),
+        Err(e) => {
+            println!("{e}");
+            std::process::exit(1);
+        }
+    }
+    match parse_filter(&matches.opt_strs("exclude")) {
+        Ok(v) => opt.exclude.extend(v),
+        Err(e) => {
+            println!("{e}");
+            std::process::exit(1);
+        }
+    }
+    if matches.opt_present("n") {
+        opt.showlnum = true;
+    }
+    if matches.opt_present("f") {
+        opt.foldline = true;
+    }
+    if matches.opt_present("noblink") {
+        opt.blinkline = false;
+    }
+    if matches.opt_present("r") {
+        opt.rotatecol = true;
+    }
+    if matches.opt_present("follow") {
+        opt.follow = true;
+    }
+    if matches.opt_present("table") {
+        opt.table = true;
+    }
+    if matches.opt_present("delta") {
+        opt.delta = delta::Mode::Delta;
+    }
+    if matches.opt_present("rate") {
+        opt.delta = delta::Mode::Rate;
+    }
+    if matches.opt_present("usedelay") {
+        opt.usedelay = true;
+    }
+    if matches.opt_present("debug") {
+        opt.debug = true;
+    }
+
+    if let Some(v) = matches.opt_str("pid") {
+        opt.pid = match v.parse::<u32>() {
+            Ok(v) => Some(v),
+            Err(e) => {
+                println!("{v}: {e}");
+                std::process::exit(1);
+            }
+        };
+    }
+    if let Some(v) = matches.opt_str("pgrep") {
+        match process::pgrep(&v) {
+            Ok(Some(x)) => opt.pid = Some(x),
+            Ok(None) => {
+                println!("No such process {v}");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                println!("{e}");
+                std::process::exit(1);
+            }
+        }
+        opt.pgrep = Some(v);
+    }
+
+    // process files first, each in a window
+    let mut args = vec![];
+    let mut procfiles = vec![];
+    if let Some(pid) = opt.pid {
+        if !process::is_alive(pid) {
+            println!("No such process {pid}");
+            std::process::exit(1);
+        }
+        procfiles = process::get_paths(pid);
+    }
+    for x in procfiles.iter().chain(&matches.free) {
+        match parse_path(x, &opt) {
+            Ok(v) => args.push(v),
+            Err(e) => {
+                println!("{e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    // process files in two columns unless layout given
+    if layout.is_empty() && procfiles.len() > 1 && args.len() == procfiles.len() {
+        layout = format!("{}{}", args.len() / 2, args.len() - args.len() / 2);
+    }
     if layout.is_empty() {
         layout = "1".repeat(args.len());
         if layout.is_empty() {
             layout = "1".to_string();
         }
     }
-    for x in layout.chars() {
-        if ('1'..='9').contains(&x) {
-            let v = if let Some(v) = x.to_digit(10) {
-                match v.try_into() {
-                    Ok(v) => v,
-                    Err(e) => {
-                        println!("invalid value {v}: {e}");
-                        std::process::exit(1);
-                    }
-                }
-            } else {
-                println!("invalid layout {layout}");
-                std::process::exit(1);
-            };
-            opt.layout.push(v);
-        } else if ('a'..='f').contains(&x) {
-            let v = if let Some(v) = x.to_digit(16) {
-                match v.try_into() {
-                    Ok(v) => v,
-                    Err(e) => {
-                        println!("invalid value {v}: {e}");
-                        std::process::exit(1);
-                    }
-                }
-            } else {
-                println!("invalid layout {layout}");
-                std::process::exit(1);
-            };
-            opt.layout.push(v);
-        } else {
-            opt.layout.push(0);
+    // session layout unless explicitly specified
+    let (layout, rotate) = match &session {
+        Some(v) if !matches.opt_present("c") => (v.layout.clone(), v.rotate),
+        _ => (layout, opt.rotatecol),
+    };
+    opt.layout = match layout::parse(&layout, rotate) {
+        Ok(v) => v,
+        Err(e) => {
+            println!("{e}");
+            std::process::exit(1);
         }
-    }
+    };
 
     if opt.debug {
-        if let Err(e) = init_file_logger(progname) {
+        if let Err(e) = init_file_logger(&home, progname) {
             println!("{e}");
             std::process::exit(1);
         }
         log::info!("{opt:?}");
     }
 
+    // keyboard input from terminal if stdin is for "-" path
+    if args.iter().any(|(f, _)| util::is_stdin(f)) {
+        if let Err(e) = util::redirect_stdin() {
+            println!("{e}");
+            std::process::exit(1);
+        }
+    }
     let attr = match screen::init_screen(opt.fgcolor, opt.bgcolor) {
         Ok(v) => v,
         Err(e) => {
@@ -295,38 +730,62 @@ fn main() {
 
     unsafe {
         libc::atexit(atexit_handler);
-        libc::signal(libc::SIGINT, sigint_handler as usize);
+        libc::signal(libc::SIGINT, sigint_handler as *const () as usize);
     }
 
-    let co = match container::Container::new(&args, attr, &opt) {
+    let mut co = match container::Container::new(&args, session.as_ref(), attr, &opt) {
         Ok(v) => v,
         Err(e) => {
             println!("{e}");
             std::process::exit(1);
         }
     };
-    let pair = std::sync::Arc::new((std::sync::Mutex::new(co), std::sync::Condvar::new()));
-    let mut thrv = container::thread_create(&pair, &opt);
+    container::thread_create(&mut co, &opt);
+    let mut fds = vec![co.get_watch_fd()];
+    if let Some(fd) = screen::get_input_fd() {
+        fds.push(fd);
+    }
     loop {
-        // XXX Do something (screen::read_incoming()) outside of co.lock(),
-        // otherwise this loop never releases the mutex, and as a result window
-        // threads get blocked without being updated.
-        let x = screen::read_incoming();
-        let (co, cv) = &*pair;
-        let mut co = co.lock().unwrap();
-        if let Err(e) = co.parse_event(x, cv, &opt) {
-            println!("{e}");
-            co.set_interrupted();
-            cv.notify_all();
-            break;
+        // block until input, file update or the next window refresh
+        let timeout = co.update_timer();
+        let v = match util::poll_read(&fds, timeout) {
+            Ok(v) => v,
+            Err(e) => {
+                println!("{e}");
+                co.set_interrupted();
+                break;
+            }
+        };
+        if v[0] {
+            if let Err(e) = co.read_watch_events() {
+                log::info!("{}: {}", util::function!(), e);
+            }
+        }
+        if let Err(e) = co.follow_process() {
+            log::info!("{}: {}", util::function!(), e);
+        }
+        // read input even if none, as signal may have queued KEY_RESIZE
+        loop {
+            let x = screen::read_incoming();
+            if x == screen::KEY_ERR {
+                break;
+            }
+            if let Err(e) = co.parse_event(x, &opt) {
+                println!("{e}");
+                co.set_interrupted();
+            }
+            if co.is_interrupted() {
+                break;
+            }
         }
         if is_interrupted() {
             co.set_interrupted();
-            cv.notify_all();
+        }
+        if co.is_interrupted() {
             break;
         }
     }
-    container::thread_join(&mut thrv);
+    container::thread_join(&mut co);
 
     log::info!(
         "{}: {:?} exit",
@@ -334,3 +793,50 @@ fn main() {
         std::thread::current().id()
     );
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_parse_path() {
+        let opt = super::Opt {
+            ..Default::default()
+        };
+        let (f, wopt) = super::parse_path("/does/not/exist", &opt).unwrap();
+        assert_eq!(f, "/does/not/exist");
+        assert_eq!(wopt.interval, 1000);
+        assert!(!wopt.showlnum);
+
+        let (f, wopt) =
+            super::parse_path("/does/not/exist:interval=200ms,lnum,fold,rate", &opt).unwrap();
+        assert_eq!(f, "/does/not/exist");
+        assert_eq!(wopt.interval, 200);
+        assert!(wopt.showlnum);
+        assert!(wopt.foldline);
+        assert!(wopt.blinkline);
+        assert_eq!(wopt.delta, super::delta::Mode::Rate);
+
+        let (f, wopt) = super::parse_path("/a:b:interval=2,noblink,tabstop=4", &opt).unwrap();
+        assert_eq!(f, "/a:b");
+        assert_eq!(wopt.interval, 2000);
+        assert!(!wopt.blinkline);
+        assert_eq!(wopt.tabstop, 4);
+
+        let (f, wopt) = super::parse_path("!date +%H:%M:timeout=500ms", &opt).unwrap();
+        assert_eq!(f, "!date +%H:%M");
+        assert_eq!(wopt.timeout, 500);
+
+        let (f, wopt) = super::parse_path("-:ring=100,table", &opt).unwrap();
+        assert_eq!(f, "-");
+        assert_eq!(wopt.ring, 100);
+        assert!(wopt.table);
+
+        for s in [
+            "/does/not/exist:fodl",
+            "/does/not/exist:interval=x",
+            "/does/not/exist:interval=1h",
+            "/does/not/exist:lnum=1",
+        ] {
+            assert!(super::parse_path(s, &opt).is_err(), "{s}");
+        }
+    }
+}
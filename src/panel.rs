@@ -13,18 +13,16 @@ pub(crate) struct Panel {
     xlen: usize,
     ypos: usize,
     xpos: usize,
+    hidden: bool,
 }
 
 pub(crate) trait PanelImpl {
     fn new(ylen: usize, xlen: usize, ypos: usize, xpos: usize, attr: &screen::Attr) -> Result<Self>
     where
         Self: Sized;
-    #[allow(dead_code)]
     fn get_ylen(&self) -> usize;
     fn get_xlen(&self) -> usize;
-    #[allow(dead_code)]
     fn get_ypos(&self) -> usize;
-    #[allow(dead_code)]
     fn get_xpos(&self) -> usize;
     fn set_title(&mut self, _s: &str) -> Result<()> {
         Ok(())
@@ -32,6 +30,7 @@ pub(crate) trait PanelImpl {
     fn set_focus(&mut self, _t: bool, _standout_attr: u32) -> Result<()> {
         Ok(())
     }
+    fn set_hidden(&mut self, t: bool); // refresh does nothing while hidden
     fn refresh(&mut self) -> Result<()>;
     fn erase(&mut self) -> Result<()>;
     fn resize(
@@ -45,6 +44,12 @@ pub(crate) trait PanelImpl {
     fn print(&self, y: usize, x: usize, standout: bool, standout_attr: u32, s: &str) -> Result<()>;
 }
 
+impl Panel {
+    pub(crate) fn erase_line(&mut self, y: usize) -> Result<()> {
+        self.scr.erase_line(y)
+    }
+}
+
 impl Drop for Panel {
     fn drop(&mut self) {
         self.scr.delete().unwrap();
@@ -65,6 +70,7 @@ impl PanelImpl for Panel {
             xlen,
             ypos,
             xpos,
+            hidden: false,
         };
         panel.scr.bkgd(attr.get_color_attr())?;
         Ok(panel)
@@ -86,7 +92,14 @@ impl PanelImpl for Panel {
         self.xpos
     }
 
+    fn set_hidden(&mut self, t: bool) {
+        self.hidden = t;
+    }
+
     fn refresh(&mut self) -> Result<()> {
+        if self.hidden {
+            return Ok(());
+        }
         self.scr.refresh()
     }
 
@@ -0,0 +1,86 @@
+use crate::util;
+
+// files under /proc/<pid> opened for --pid and --pgrep
+const FILE_LIST: [&str; 5] = ["status", "io", "sched", "limits", "smaps_rollup"];
+
+// Paths of files in FILE_LIST for pid, except those not readable, e.g. io of
+// process owned by other user.
+pub(crate) fn get_paths(pid: u32) -> Vec<String> {
+    FILE_LIST
+        .iter()
+        .map(|f| format!("/proc/{pid}/{f}"))
+        .filter(|f| std::fs::File::open(f).is_ok())
+        .collect()
+}
+
+// pid of "/proc/<pid>/..." path
+pub(crate) fn get_pid(f: &str) -> Option<u32> {
+    let (s, _) = f.strip_prefix("/proc/")?.split_once('/')?;
+    s.parse().ok()
+}
+
+pub(crate) fn is_alive(pid: u32) -> bool {
+    std::path::Path::new(&format!("/proc/{pid}")).exists()
+}
+
+// pid of the oldest process named name other than self, where name is
+// compared within length of comm
+pub(crate) fn pgrep(name: &str) -> std::io::Result<Option<u32>> {
+    let name: String = name.chars().take(15).collect();
+    let mut found: Option<(u64, u32)> = None;
+    for x in std::fs::read_dir("/proc")? {
+        let Some(pid) = x?.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+            continue;
+        };
+        if pid == std::process::id() {
+            continue;
+        }
+        // process may exit meanwhile
+        let Ok(s) = std::fs::read_to_string(format!("/proc/{pid}/stat")) else {
+            continue;
+        };
+        let Some((comm, t)) = parse_stat(&s) else {
+            continue;
+        };
+        if comm == name && found.is_none_or(|x| t < x.0) {
+            found = Some((t, pid));
+        }
+    }
+    log::info!("{}: {} {:?}", util::function!(), name, found);
+    Ok(found.map(|x| x.1))
+}
+
+// (comm, starttime) of /proc/<pid>/stat contents
+fn parse_stat(s: &str) -> Option<(&str, u64)> {
+    let (_, b) = s.split_once(" (")?;
+    let (comm, b) = b.rsplit_once(") ")?;
+    // starttime is the 22nd field, and the 3rd field follows comm
+    let t = b.split_whitespace().nth(22 - 3)?.parse().ok()?;
+    Some((comm, t))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_get_pid() {
+        assert_eq!(super::get_pid("/proc/123/status"), Some(123));
+        assert_eq!(super::get_pid("/proc/self/status"), None);
+        assert_eq!(super::get_pid("/proc/meminfo"), None);
+        assert_eq!(super::get_pid("/tmp/123/status"), None);
+    }
+
+    #[test]
+    fn test_parse_stat() {
+        let s = "42 (a) b) S 1 42 42 0 -1 4194560 100 0 0 0 1 2 0 0 20 0 1 0 12345 0 0";
+        assert_eq!(super::parse_stat(s), Some(("a) b", 12345)));
+        assert_eq!(super::parse_stat("42 a"), None);
+    }
+
+    #[test]
+    fn test_pgrep() {
+        let pid = std::process::id();
+        assert!(super::is_alive(pid));
+        assert!(!super::get_paths(pid).is_empty());
+        assert_eq!(super::pgrep("no-such-process-name").unwrap(), None);
+    }
+}
@@ -0,0 +1,74 @@
+// per window states, where paths are buffer list of the window
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct WindowSession {
+    pub(crate) paths: Vec<String>,
+    pub(crate) current: usize,
+    pub(crate) offset: usize,
+    pub(crate) follow: bool,
+    pub(crate) include: Option<String>,
+    pub(crate) exclude: Option<String>,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Session {
+    pub(crate) layout: String,
+    pub(crate) rotate: bool,
+    pub(crate) focus: usize,
+    pub(crate) window: Vec<WindowSession>,
+}
+
+// Missing file is not an error, as it is yet to be saved.
+pub(crate) fn load(f: &str) -> crate::Result<Option<Session>> {
+    let s = match std::fs::read_to_string(f) {
+        Ok(v) => v,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(Box::new(e)),
+    };
+    Ok(Some(toml::from_str(&s)?))
+}
+
+pub(crate) fn save(s: &Session, f: &str) -> crate::Result<()> {
+    Ok(std::fs::write(f, toml::to_string(s)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_save() {
+        let f = std::env::temp_dir().join(format!("procstat-rs-session-{}", std::process::id()));
+        let f = f.to_str().unwrap();
+        assert!(super::load(f).unwrap().is_none());
+
+        let s = super::Session {
+            layout: "67:1,33:2".to_string(),
+            rotate: false,
+            focus: 1,
+            window: vec![
+                super::WindowSession {
+                    paths: vec!["/proc/meminfo".to_string(), "/does/not/exist".to_string()],
+                    current: 1,
+                    offset: 10,
+                    follow: true,
+                    include: Some("^Mem".to_string()),
+                    exclude: None,
+                },
+                super::WindowSession::default(),
+            ],
+        };
+        super::save(&s, f).unwrap();
+        let t = super::load(f).unwrap().unwrap();
+        std::fs::remove_file(f).unwrap();
+        assert_eq!(t.layout, s.layout);
+        assert_eq!(t.focus, 1);
+        assert_eq!(t.window.len(), 2);
+        assert_eq!(t.window[0].paths, s.window[0].paths);
+        assert_eq!(t.window[0].current, 1);
+        assert_eq!(t.window[0].offset, 10);
+        assert!(t.window[0].follow);
+        assert_eq!(t.window[0].include.as_deref(), Some("^Mem"));
+        assert!(t.window[0].exclude.is_none());
+        assert!(t.window[1].paths.is_empty());
+    }
+}
@@ -14,6 +14,8 @@ pub(crate) const KEY_DOWN: u32 = KEY_BASE + 1;
 pub(crate) const KEY_LEFT: u32 = KEY_BASE + 2;
 pub(crate) const KEY_RIGHT: u32 = KEY_BASE + 3;
 pub(crate) const KEY_RESIZE: u32 = KEY_BASE + 4;
+pub(crate) const KEY_BACKSPACE: u32 = KEY_BASE + 5;
+pub(crate) const KEY_ENTER: u32 = KEY_BASE + 6;
 
 pub(crate) fn key_ctrl(x: u32) -> u32 {
     x & 0x1F
@@ -49,6 +51,10 @@ impl Attr {
     pub(crate) fn get_standout_attr(&self) -> u32 {
         0
     }
+
+    pub(crate) fn get_search_attr(&self) -> u32 {
+        0
+    }
 }
 
 #[derive(Debug)]
@@ -81,15 +87,21 @@ pub(crate) fn cleanup_screen() -> Result<()> {
 }
 
 pub(crate) fn read_incoming() -> i32 {
-    std::thread::sleep(std::time::Duration::from_secs(1));
     KEY_ERR
 }
 
+// no input to poll
+pub(crate) fn get_input_fd() -> Option<i32> {
+    None
+}
+
 pub(crate) fn clear_terminal() -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn flash_terminal() {}
+pub(crate) fn flash_terminal() -> Result<()> {
+    Ok(())
+}
 
 pub(crate) fn alloc_screen(ylen: usize, xlen: usize, ypos: usize, xpos: usize) -> Result<Screen> {
     let _mtx = MTX.lock()?;
@@ -159,6 +171,14 @@ impl Screen {
         Ok(())
     }
 
+    pub(crate) fn erase_line(&mut self, _y: usize) -> Result<()> {
+        Ok(())
+    }
+
+    pub(crate) fn untouch_lines(&mut self, _y: usize, _n: usize) -> Result<()> {
+        Ok(())
+    }
+
     pub(crate) fn resize(&mut self, _ylen: usize, _xlen: usize) -> Result<()> {
         Ok(())
     }
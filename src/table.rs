@@ -0,0 +1,179 @@
+use crate::util;
+
+const UNIT_LIST: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+// Render "key: value" or "key value" lines in columns, whose widths only grow
+// so that alignment is kept across refreshes.
+#[derive(Debug, Default)]
+pub(crate) struct Table {
+    enabled: bool,
+    kwidth: usize, // key column
+    nwidth: usize, // number column, right aligned
+}
+
+impl Table {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn set_enabled(&mut self, t: bool) {
+        self.enabled = t;
+        self.reset();
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.kwidth = 0;
+        self.nwidth = 0;
+    }
+
+    // Widen columns for line s ahead of format(), so that all lines painted
+    // are aligned.
+    pub(crate) fn measure(&mut self, s: &str) {
+        if let Some((k, v)) = split_line(s) {
+            self.kwidth = self.kwidth.max(util::get_width(k));
+            if let Some((n, _)) = split_value(v) {
+                self.nwidth = self.nwidth.max(n.len());
+            }
+        }
+    }
+
+    // Return s in columns as measured so far if a key value line, otherwise
+    // s as is.
+    pub(crate) fn format(&self, s: &str) -> String {
+        if !self.enabled {
+            return s.to_string();
+        }
+        let Some((k, v)) = split_line(s) else {
+            return s.to_string();
+        };
+        let pad = " ".repeat(self.kwidth.saturating_sub(util::get_width(k)));
+        match split_value(v) {
+            Some((n, Some(u))) => format!("{k}{pad} {n:>w$} {u}", w = self.nwidth),
+            Some((n, None)) => format!("{k}{pad} {n:>w$}", w = self.nwidth),
+            None => format!("{k}{pad} {v}"),
+        }
+    }
+}
+
+// (key, value) of line whose first token is "key:", or which is followed by
+// ": value" or a numeric value, e.g. /proc/meminfo, /proc/<pid>/sched and
+// /proc/vmstat lines respectively
+fn split_line(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_end();
+    let i = s.find(char::is_whitespace)?;
+    let (k, v) = s.split_at(i);
+    let v = v.trim_start();
+    if let Some(t) = v.strip_prefix(':') {
+        let t = t.trim_start();
+        return (!t.is_empty()).then_some((k, t));
+    }
+    if k.len() > 1 && k.ends_with(':') || split_value(v).is_some() {
+        return Some((k, v));
+    }
+    None
+}
+
+// (number, unit) of "<number>" or "<number> kB" value, with kB humanized
+fn split_value(v: &str) -> Option<(String, Option<&'static str>)> {
+    let (n, u) = match v.split_once(char::is_whitespace) {
+        Some((n, u)) => (n, Some(u.trim_start())),
+        None => (v, None),
+    };
+    match u {
+        Some("kB") => {
+            let x = n.parse::<i64>().ok()?;
+            let (n, u) = humanize(x);
+            Some((n, Some(u)))
+        }
+        Some(_) => None,
+        None => n.parse::<f64>().is_ok().then(|| (n.to_string(), None)),
+    }
+}
+
+// x kB in the largest unit with its integral part below 1024
+fn humanize(x: i64) -> (String, &'static str) {
+    let mut f = x as f64;
+    let mut i = 0;
+    while f.abs() >= 1024.0 && i < UNIT_LIST.len() - 1 {
+        f /= 1024.0;
+        i += 1;
+    }
+    if i == 0 {
+        (x.to_string(), UNIT_LIST[0])
+    } else {
+        (format!("{f:.1}"), UNIT_LIST[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn render(t: &mut super::Table, s: &str) -> String {
+        t.measure(s);
+        t.format(s)
+    }
+
+    #[test]
+    fn test_render() {
+        let mut t = super::Table::new();
+        assert_eq!(
+            render(&mut t, "MemTotal: 16318480 kB"),
+            "MemTotal: 16318480 kB"
+        );
+        t.set_enabled(true);
+        for s in [
+            "MemTotal:       16318480 kB",
+            "Hugepagesize:       2048 kB",
+            "HugePages_Total:       0",
+            "Name:\tbash",
+        ] {
+            t.measure(s);
+        }
+        assert_eq!(
+            render(&mut t, "MemTotal:       16318480 kB"),
+            "MemTotal:        15.6 GiB"
+        );
+        assert_eq!(
+            render(&mut t, "Hugepagesize:       2048 kB"),
+            "Hugepagesize:     2.0 MiB"
+        );
+        assert_eq!(
+            render(&mut t, "HugePages_Total:       0"),
+            "HugePages_Total:    0"
+        );
+        assert_eq!(render(&mut t, "Name:\tbash"), "Name:            bash");
+        assert_eq!(render(&mut t, "nr_free_pages 12"), "nr_free_pages      12");
+        assert_eq!(
+            render(&mut t, "se.vruntime  :  1.5"),
+            "se.vruntime       1.5"
+        );
+        assert_eq!(render(&mut t, "Limit  Soft Limit"), "Limit  Soft Limit");
+        assert_eq!(
+            render(&mut t, "VmLck:         512 kB"),
+            "VmLck:            512 KiB"
+        );
+
+        // widened by longer line, and never narrowed
+        assert_eq!(
+            render(&mut t, "a_very_long_key_name: 1"),
+            "a_very_long_key_name:    1"
+        );
+        assert_eq!(render(&mut t, "x: 1"), "x:                       1");
+        t.reset();
+        // not measured yet, e.g. searched beyond lines painted
+        assert_eq!(t.format("a_key: 1"), "a_key: 1");
+        assert_eq!(render(&mut t, "x: 1"), "x: 1");
+    }
+
+    #[test]
+    fn test_humanize() {
+        assert_eq!(super::humanize(0), ("0".to_string(), "KiB"));
+        assert_eq!(super::humanize(1023), ("1023".to_string(), "KiB"));
+        assert_eq!(super::humanize(1024), ("1.0".to_string(), "MiB"));
+        assert_eq!(super::humanize(-2048), ("-2.0".to_string(), "MiB"));
+        assert_eq!(super::humanize(3 << 30), ("3.0".to_string(), "TiB"));
+    }
+}
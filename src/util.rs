@@ -1,4 +1,9 @@
 use path_clean::PathClean;
+use std::os::fd::AsFd;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::FileTypeExt;
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
 
 macro_rules! function {
     () => {{
@@ -19,10 +24,61 @@ pub(crate) fn is_regular_file(f: &str) -> bool {
     }
 }
 
+pub(crate) fn is_fifo(f: &str) -> bool {
+    if let Ok(v) = std::fs::metadata(f) {
+        v.file_type().is_fifo()
+    } else {
+        false
+    }
+}
+
+// "-" path for stdin
+pub(crate) fn is_stdin(f: &str) -> bool {
+    f == "-"
+}
+
+// stdin moved aside by redirect_stdin()
+static STDIN_FD: std::sync::OnceLock<std::os::fd::OwnedFd> = std::sync::OnceLock::new();
+
+// Move stdin to another fd, and reopen terminal as stdin for keyboard input.
+// Fails if stdin is a terminal, as there is nothing to read for "-" path.
+pub(crate) fn redirect_stdin() -> std::io::Result<()> {
+    if unsafe { libc::isatty(libc::STDIN_FILENO) } == 1 {
+        return Err(stdin_is_terminal());
+    }
+    let fd = std::io::stdin().as_fd().try_clone_to_owned()?;
+    let tty = std::fs::File::open("/dev/tty")?;
+    if unsafe { libc::dup2(tty.as_raw_fd(), libc::STDIN_FILENO) } < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    STDIN_FD.set(fd).map_err(|_| error())
+}
+
+// path to open stdin moved aside
+pub(crate) fn get_stdin_path() -> std::io::Result<String> {
+    STDIN_FD
+        .get()
+        .map(|fd| format!("/proc/self/fd/{}", fd.as_raw_fd()))
+        .ok_or_else(stdin_is_terminal)
+}
+
+fn stdin_is_terminal() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, "stdin is a terminal")
+}
+
+// command line of "!<command>" path
+pub(crate) fn get_command(f: &str) -> Option<&str> {
+    f.strip_prefix('!')
+}
+
 // This function
 // * does not resolve symlink
 // * works with non existent path
+// * returns command path and stdin as is
 pub(crate) fn get_abspath(f: &str) -> std::io::Result<String> {
+    if get_command(f).is_some() || is_stdin(f) {
+        return Ok(f.to_string());
+    }
     let p = std::path::Path::new(f);
     if p.is_absolute() {
         p.to_path_buf()
@@ -45,6 +101,28 @@ pub(crate) fn get_basename(f: &str) -> std::io::Result<String> {
         .to_string())
 }
 
+// File on procfs, sysfs and alike, whose contents are generated on read
+// without size or inotify events.
+pub(crate) fn is_pseudo_file(f: &str) -> bool {
+    let Ok(p) = std::ffi::CString::new(f) else {
+        return false;
+    };
+    let mut st: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(p.as_ptr(), &mut st) } != 0 {
+        return false;
+    }
+    [
+        libc::PROC_SUPER_MAGIC,
+        libc::SYSFS_MAGIC,
+        libc::DEBUGFS_MAGIC,
+        libc::TRACEFS_MAGIC,
+        libc::CGROUP_SUPER_MAGIC,
+        libc::CGROUP2_SUPER_MAGIC,
+        libc::SECURITYFS_MAGIC,
+    ]
+    .contains(&st.f_type)
+}
+
 pub(crate) fn is_dir(f: &str) -> bool {
     if let Ok(v) = std::fs::metadata(f) {
         v.file_type().is_dir()
@@ -53,6 +131,22 @@ pub(crate) fn is_dir(f: &str) -> bool {
     }
 }
 
+// Glob pattern for path, i.e. "<dir>/*" for directory, or absolute path if
+// it has wildcards and doesn't exist as is.
+pub(crate) fn get_glob(f: &str) -> std::io::Result<Option<String>> {
+    if get_command(f).is_some() || is_stdin(f) {
+        return Ok(None);
+    }
+    let abspath = get_abspath(f)?;
+    if is_dir(&abspath) {
+        return Ok(Some(join_path(&abspath, "*")));
+    }
+    if !std::path::Path::new(&abspath).exists() && abspath.contains(['*', '?', '[']) {
+        return Ok(Some(abspath));
+    }
+    Ok(None)
+}
+
 pub(crate) fn join_path(f1: &str, f2: &str) -> String {
     std::path::Path::new(f1)
         .join(f2)
@@ -74,6 +168,115 @@ pub(crate) fn error() -> std::io::Error {
     std::io::Error::from(std::io::ErrorKind::Other)
 }
 
+// Wait until any of fds is readable or timeout, and return whether each is
+// readable. Interrupted by signal is not an error, with none readable.
+pub(crate) fn poll_read(
+    fds: &[i32],
+    timeout: Option<std::time::Duration>,
+) -> std::io::Result<Vec<bool>> {
+    let mut v: Vec<libc::pollfd> = fds
+        .iter()
+        .map(|&fd| libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect();
+    // round up, or it wakes up before timer expires
+    let t = match timeout {
+        Some(d) => i32::try_from(d.as_micros().div_ceil(1000)).unwrap_or(i32::MAX),
+        None => -1,
+    };
+    let n = libc::nfds_t::try_from(v.len()).map_err(|_| error())?;
+    if unsafe { libc::poll(v.as_mut_ptr(), n, t) } < 0 {
+        let e = std::io::Error::last_os_error();
+        if e.kind() == std::io::ErrorKind::Interrupted {
+            return Ok(vec![false; fds.len()]);
+        }
+        return Err(e);
+    }
+    Ok(v.iter().map(|x| x.revents != 0).collect())
+}
+
+// Convert raw line into printable string.
+// * invalid UTF-8 is replaced with U+FFFD
+// * tab is expanded to next multiple of tabstop, or "^I" if tabstop is 0
+// * other control characters are shown in caret notation, e.g. "^[" for ESC
+pub(crate) fn sanitize_line(b: &[u8], tabstop: usize) -> String {
+    let mut s = String::new();
+    let mut w = 0;
+    for c in String::from_utf8_lossy(b).chars() {
+        let x = u32::from(c);
+        if c == '\t' && tabstop != 0 {
+            let n = tabstop - w % tabstop;
+            s.push_str(&" ".repeat(n));
+            w += n;
+        } else if x < 0x20 || x == 0x7F {
+            s.push('^');
+            s.push(char::from_u32(x ^ 0x40).unwrap_or(char::REPLACEMENT_CHARACTER));
+            w += 2;
+        } else if c.is_control() {
+            s.push(char::REPLACEMENT_CHARACTER);
+            w += 1;
+        } else {
+            s.push(c);
+            w += c.width().unwrap_or(0);
+        }
+    }
+    s
+}
+
+pub(crate) fn get_width(s: &str) -> usize {
+    s.width()
+}
+
+// w columns of s starting from column x, wide character cut at x is padded
+pub(crate) fn slice_width(s: &str, x: usize, w: usize) -> String {
+    let mut n = 0;
+    for (i, c) in s.char_indices() {
+        if n >= x {
+            return truncate_width(&s[i..], w).to_string();
+        }
+        n += c.width().unwrap_or(0);
+        if n > x {
+            let t = " ".repeat((n - x).min(w));
+            let i = i + c.len_utf8();
+            return t.clone() + truncate_width(&s[i..], w - t.len());
+        }
+    }
+    String::new()
+}
+
+// longest prefix of s which fits in w columns
+pub(crate) fn truncate_width(s: &str, w: usize) -> &str {
+    let mut n = 0;
+    for (i, c) in s.char_indices() {
+        n += c.width().unwrap_or(0);
+        if n > w {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+// split s into rows which fit in w columns, wide character is never split
+pub(crate) fn split_width(s: &str, w: usize) -> Vec<&str> {
+    let mut v = vec![];
+    let mut t = s;
+    while !t.is_empty() {
+        let mut x = truncate_width(t, w);
+        if x.is_empty() {
+            x = &t[..t.chars().next().map_or(t.len(), char::len_utf8)];
+        }
+        v.push(x);
+        t = &t[x.len()..];
+    }
+    if v.is_empty() {
+        v.push(s);
+    }
+    v
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -116,6 +319,11 @@ mod tests {
                 i: "/does/not/../NOT/exist",
                 o: "/does/NOT/exist",
             },
+            F {
+                i: "!ls ../x",
+                o: "!ls ../x",
+            },
+            F { i: "-", o: "-" },
         ];
         for x in &path_list {
             match super::get_abspath(x.i) {
@@ -124,4 +332,81 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_sanitize_line() {
+        let line_list = [
+            (&b""[..], 8, ""),
+            (b"abc", 8, "abc"),
+            (b"a\tb", 8, "a       b"),
+            (b"a\tb", 4, "a   b"),
+            (b"a\tb", 0, "a^Ib"),
+            ("\u{3042}\tb".as_bytes(), 4, "\u{3042}  b"),
+            (b"a\x1b[0mb", 8, "a^[[0mb"),
+            (b"a\x7fb", 8, "a^?b"),
+            (b"a\xffb", 8, "a\u{FFFD}b"),
+            ("a\u{85}b".as_bytes(), 8, "a\u{FFFD}b"),
+        ];
+        for (i, t, o) in &line_list {
+            assert_eq!(super::sanitize_line(i, *t), *o, "{i:?}");
+        }
+    }
+
+    #[test]
+    fn test_is_pseudo_file() {
+        assert!(super::is_pseudo_file("/proc/meminfo"));
+        assert!(super::is_pseudo_file("/proc/self/stat"));
+        assert!(!super::is_pseudo_file(env!("CARGO_MANIFEST_DIR")));
+        assert!(!super::is_pseudo_file("/does/not/exist"));
+    }
+
+    #[test]
+    fn test_get_glob() {
+        let d = env!("CARGO_MANIFEST_DIR");
+        assert_eq!(super::get_glob(d).unwrap(), Some(format!("{d}/*")));
+        assert_eq!(
+            super::get_glob("/does/not/*.log").unwrap().as_deref(),
+            Some("/does/not/*.log")
+        );
+        assert_eq!(super::get_glob("/does/not/exist").unwrap(), None);
+        assert_eq!(super::get_glob("!ls *").unwrap(), None);
+    }
+
+    #[test]
+    fn test_poll_read() {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let d = std::time::Duration::from_millis(10);
+        assert_eq!(super::poll_read(&fds[..1], Some(d)).unwrap(), vec![false]);
+        assert_eq!(unsafe { libc::write(fds[1], b"x".as_ptr().cast(), 1) }, 1);
+        assert_eq!(super::poll_read(&fds[..1], None).unwrap(), vec![true]);
+        unsafe {
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_split_width() {
+        assert_eq!(super::truncate_width("abcdef", 4), "abcd");
+        assert_eq!(super::truncate_width("ab", 4), "ab");
+        assert_eq!(
+            super::truncate_width("\u{3042}\u{3044}\u{3046}", 5),
+            "\u{3042}\u{3044}"
+        );
+        assert_eq!(super::truncate_width("e\u{301}x", 1), "e\u{301}");
+        assert_eq!(super::slice_width("abcdef", 2, 3), "cde");
+        assert_eq!(super::slice_width("abcdef", 4, 3), "ef");
+        assert_eq!(super::slice_width("abcdef", 6, 3), "");
+        assert_eq!(super::slice_width("\u{3042}\u{3044}b", 1, 3), " \u{3044}");
+        assert_eq!(super::slice_width("\u{3042}\u{3044}b", 2, 3), "\u{3044}b");
+        assert_eq!(super::get_width("a\u{3042}e\u{301}"), 4);
+        assert_eq!(super::split_width("", 4), [""]);
+        assert_eq!(super::split_width("abcdef", 4), ["abcd", "ef"]);
+        assert_eq!(
+            super::split_width("a\u{3042}\u{3044}b", 2),
+            ["a", "\u{3042}", "\u{3044}", "b"]
+        );
+        assert_eq!(super::split_width("\u{3042}", 1), ["\u{3042}"]);
+    }
 }
@@ -1,9 +1,13 @@
 use crate::buffer;
+use crate::delta;
 use crate::frame;
 use crate::panel;
 use crate::panel::PanelImpl;
+use crate::session;
+use crate::table;
 use crate::util;
 use crate::Result;
+use crate::WindowOpt;
 
 #[cfg(feature = "curses")]
 use crate::curses as screen;
@@ -11,12 +15,58 @@ use crate::curses as screen;
 #[cfg(feature = "stdout")]
 use crate::stdout as screen;
 
+#[derive(Debug, Default)]
+struct Filter {
+    include: Option<regex::Regex>,
+    exclude: Option<regex::Regex>,
+}
+
+impl Filter {
+    fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_none()
+    }
+
+    fn is_match(&self, s: &str) -> bool {
+        self.include.as_ref().is_none_or(|re| re.is_match(s))
+            && self.exclude.as_ref().is_none_or(|re| !re.is_match(s))
+    }
+}
+
+// (x, standout, standout attribute, string) printed on a row
+type Row = Vec<(usize, bool, u32, String)>;
+
+// Last lines of buffer which pass the filter and fill the panel, kept for
+// follow mode so that only lines appended since are read on update.
+#[derive(Debug, Default)]
+struct Tail {
+    key: Option<(u64, usize, usize, bool, bool)>, // buffer epoch and view
+    scanned: usize,                               // lines read so far
+    lines: std::collections::VecDeque<(usize, usize)>, // (pos, rows)
+    rows: usize,
+}
+
 #[derive(Debug)]
 pub(crate) struct Window {
     panel: panel::Panel,
     frame: frame::Frame,
     buffer: buffer::Buffer,
+    bufv: Vec<(buffer::Buffer, WindowOpt)>, // current one moved to buffer
+    bi: usize,
+    interval: u64,
+    showlnum: bool,
+    foldline: bool,
+    blinkline: bool,
     offset: usize,
+    xoffset: usize,
+    xmax: usize,
+    follow: bool,
+    search: Option<regex::Regex>,
+    search_forward: bool,
+    filter: Filter,
+    delta: delta::Delta,
+    table: table::Table,
+    painted: Vec<Row>, // rows on panel, empty if to be repainted entirely
+    tail: Tail,
 }
 
 impl Window {
@@ -31,17 +81,39 @@ impl Window {
             frame: frame::Frame::new(ylen, xlen, ypos, xpos, attr)?,
             panel: panel::Panel::new(ylen - 2, xlen - 2, ypos + 1, xpos + 1, attr)?,
             buffer: buffer::Buffer::new()?,
+            bufv: Vec::new(),
+            bi: 0,
+            interval: 1000,
+            showlnum: false,
+            foldline: false,
+            blinkline: true,
             offset: 0,
+            xoffset: 0,
+            xmax: 0,
+            follow: false,
+            search: None,
+            search_forward: true,
+            filter: Filter::default(),
+            delta: delta::Delta::new(),
+            table: table::Table::new(),
+            painted: Vec::new(),
+            tail: Tail::default(),
         };
         w.frame.refresh()?;
         w.panel.refresh()?;
         Ok(w)
     }
 
-    pub(crate) fn is_dead(&mut self) -> bool {
+    pub(crate) fn is_dead(&self) -> bool {
         self.buffer.is_dead()
     }
 
+    // Current buffer needs refresh every interval, as it has no inotify
+    // events.
+    pub(crate) fn is_timed(&self) -> bool {
+        !self.is_dead() && self.buffer.is_pseudo()
+    }
+
     pub(crate) fn resize(
         &mut self,
         ylen: usize,
@@ -53,21 +125,295 @@ impl Window {
         self.frame.resize(ylen, xlen, ypos, xpos, attr)?;
         self.panel
             .resize(ylen - 2, xlen - 2, ypos + 1, xpos + 1, attr)?;
+        self.painted.clear();
         self.offset = 0;
+        self.xoffset = 0;
+        if self.follow {
+            self.goto_tail()?;
+        }
         Ok(())
     }
 
-    pub(crate) fn attach_buffer(&mut self, f: &str) -> Result<()> {
-        self.buffer.init(f)?; // still had no path set at this point
-        self.frame.set_title(f)?;
-        self.panel.set_title(f)?;
+    fn new_buffer(f: &str, wopt: &WindowOpt) -> Result<buffer::Buffer> {
+        let mut b = buffer::Buffer::new()?;
+        b.set_tabstop(wopt.tabstop);
+        b.set_timeout(wopt.get_timeout());
+        b.set_ring(wopt.ring);
+        b.init(f)?; // still had no path set at this point
+        Ok(b)
+    }
+
+    // Append path to buffer list and make it current.
+    pub(crate) fn attach_buffer(&mut self, f: &str, wopt: &WindowOpt) -> Result<()> {
+        let b = Self::new_buffer(f, wopt)?;
+        self.bufv.push((b, wopt.clone()));
+        self.select_buffer(self.bufv.len() - 1)?;
         log::info!("{}: {:?} {:?}", util::function!(), self.panel, self.frame,);
         Ok(())
     }
 
-    pub(crate) fn update_buffer(&mut self) -> std::io::Result<()> {
-        self.buffer.update()?;
+    // Remove current buffer from buffer list, and select the next one if any.
+    pub(crate) fn detach_buffer(&mut self) -> Result<()> {
+        if self.is_dead() {
+            return Ok(());
+        }
+        self.bufv.remove(self.bi);
+        self.buffer = buffer::Buffer::new()?;
+        self.reset_view()?;
+        if self.bufv.is_empty() {
+            self.bi = 0;
+            self.update_title()?;
+            self.panel.erase()?;
+            self.painted.clear();
+            return self.panel.refresh();
+        }
+        self.select_buffer(self.bi.min(self.bufv.len() - 1))
+    }
+
+    fn select_buffer(&mut self, i: usize) -> Result<()> {
+        if !self.is_dead() {
+            std::mem::swap(&mut self.buffer, &mut self.bufv[self.bi].0);
+        }
+        self.bi = i;
+        std::mem::swap(&mut self.buffer, &mut self.bufv[self.bi].0);
+        self.reset_view()?;
+        let wopt = self.bufv[self.bi].1.clone();
+        self.set_options(&wopt)
+    }
+
+    // Select buffer d ahead in buffer list.
+    pub(crate) fn goto_buffer(&mut self, d: isize) -> Result<()> {
+        let n = isize::try_from(self.bufv.len())?;
+        if n <= 1 {
+            return Ok(());
+        }
+        let i = (isize::try_from(self.bi)? + d).rem_euclid(n);
+        self.select_buffer(usize::try_from(i)?)
+    }
+
+    pub(crate) fn goto_first_buffer(&mut self) -> Result<()> {
+        if self.bufv.len() > 1 && self.bi != 0 {
+            self.select_buffer(0)?;
+        }
+        Ok(())
+    }
+
+    // Select buffer for path if in buffer list.
+    pub(crate) fn goto_path(&mut self, f: &str) -> Result<bool> {
+        match self.find_buffer(f) {
+            Some(i) => {
+                if i != self.bi {
+                    self.select_buffer(i)?;
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // paths in buffer list
+    pub(crate) fn get_paths(&self) -> Vec<String> {
+        if self.is_dead() {
+            return vec![];
+        }
+        (0..self.bufv.len())
+            .map(|i| {
+                if i == self.bi {
+                    self.buffer.get_path()
+                } else {
+                    self.bufv[i].0.get_path()
+                }
+                .to_string()
+            })
+            .collect()
+    }
+
+    // Replace buffer for path f with one for path g in place, e.g. for
+    // restarted process, keeping view but numbers for delta.
+    pub(crate) fn replace_path(&mut self, f: &str, g: &str) -> Result<bool> {
+        let Some(i) = self.find_buffer(f) else {
+            return Ok(false);
+        };
+        let b = Self::new_buffer(g, &self.bufv[i].1)?;
+        if i != self.bi {
+            self.bufv[i].0 = b;
+            return Ok(true);
+        }
+        self.buffer = b;
+        self.tail = Tail::default();
+        let mode = self.delta.get_mode();
+        self.delta = delta::Delta::new();
+        self.delta.set_mode(mode);
+        // offset is checked once read by update_buffer()
+        self.update_title()?;
+        Ok(true)
+    }
+
+    pub(crate) fn has_path(&self, f: &str) -> bool {
+        self.find_buffer(f).is_some()
+    }
+
+    fn find_buffer(&self, f: &str) -> Option<usize> {
+        if self.is_dead() {
+            return None;
+        }
+        (0..self.bufv.len()).find(|&i| {
+            if i == self.bi {
+                self.buffer.get_path() == f
+            } else {
+                self.bufv[i].0.get_path() == f
+            }
+        })
+    }
+
+    // Drop states tied to content of current buffer.
+    fn reset_view(&mut self) -> Result<()> {
+        self.offset = 0;
+        self.xoffset = 0;
+        self.xmax = 0;
+        self.follow = false;
+        self.search = None;
+        self.filter = Filter::default();
+        self.delta = delta::Delta::new();
+        self.table.reset();
+        self.tail = Tail::default();
+        self.frame.set_clip(false, false)
+    }
+
+    pub(crate) fn set_options(&mut self, wopt: &WindowOpt) -> Result<()> {
+        self.interval = wopt.interval;
+        self.showlnum = wopt.showlnum;
+        self.foldline = wopt.foldline;
+        self.blinkline = wopt.blinkline;
+        self.buffer.set_tabstop(wopt.tabstop);
+        self.buffer.set_timeout(wopt.get_timeout());
+        self.buffer.set_ring(wopt.ring);
+        self.filter.include.clone_from(&wopt.include);
+        self.filter.exclude.clone_from(&wopt.exclude);
+        self.tail = Tail::default();
+        self.delta.set_mode(wopt.delta);
+        self.table.set_enabled(wopt.table);
+        self.follow = wopt.follow;
+        if self.follow {
+            self.goto_tail()?;
+        }
+        if self.is_dead() {
+            return Ok(());
+        }
+        self.update_title()
+    }
+
+    pub(crate) fn get_interval(&self) -> u64 {
+        self.interval
+    }
+
+    pub(crate) fn get_path(&self) -> &str {
+        self.buffer.get_path()
+    }
+
+    // Returns true if the buffer for path reopened it.
+    pub(crate) fn update_buffer(&mut self, f: &str) -> Result<bool> {
+        let Some(i) = self.find_buffer(f) else {
+            return Ok(false);
+        };
+        if i != self.bi {
+            return Ok(self.bufv[i].0.check()?);
+        }
+        let state = self.buffer.get_state();
+        let status = self.buffer.get_status();
+        let reopened = self.buffer.check()?;
+        if reopened || self.offset > self.buffer.get_max_line() {
+            self.offset = 0;
+        }
+        if self.follow {
+            self.goto_tail()?;
+        }
+        // exit status of command shown in title too
+        if self.buffer.get_state() != state || self.buffer.get_status() != status {
+            self.update_title()?;
+        }
         log::info!("{}: {:?} {:?}", util::function!(), self.panel, self.frame,);
+        Ok(reopened)
+    }
+
+    // (path, timeout) of buffers to fetch() before update_buffer() for paths
+    // and refresh_buffer() if refresh, as fetching is done without the lock.
+    pub(crate) fn get_fetch_list(
+        &self,
+        paths: &[String],
+        refresh: bool,
+    ) -> Vec<(String, std::time::Duration)> {
+        let mut v: Vec<(String, std::time::Duration)> = vec![];
+        let f = self.buffer.get_path();
+        let l = paths.iter().map(String::as_str);
+        for f in l.chain(refresh.then_some(f)) {
+            let Some(i) = self.find_buffer(f) else {
+                continue;
+            };
+            let b = if i == self.bi {
+                &self.buffer
+            } else {
+                &self.bufv[i].0
+            };
+            if let Some(t) = b.get_fetch_timeout() {
+                if !v.iter().any(|x| x.0 == f) {
+                    v.push((f.to_string(), t));
+                }
+            }
+        }
+        v
+    }
+
+    pub(crate) fn set_fetched(&mut self, f: &str, x: std::io::Result<buffer::Fetched>) {
+        match self.find_buffer(f) {
+            Some(i) if i == self.bi => self.buffer.set_fetched(x),
+            Some(i) => self.bufv[i].0.set_fetched(x),
+            None => (),
+        }
+    }
+
+    // Read current buffer again if its file has no inotify events, i.e. pseudo
+    // file, which is done every interval.
+    pub(crate) fn refresh_buffer(&mut self) -> Result<()> {
+        if !self.buffer.is_pseudo() {
+            return Ok(());
+        }
+        let f = self.buffer.get_path().to_string();
+        self.update_buffer(&f)?;
+        Ok(())
+    }
+
+    // (ylen, xlen, ypos, xpos) of frame
+    pub(crate) fn get_geometry(&self) -> (usize, usize, usize, usize) {
+        (
+            self.frame.get_ylen(),
+            self.frame.get_xlen(),
+            self.frame.get_ypos(),
+            self.frame.get_xpos(),
+        )
+    }
+
+    pub(crate) fn get_panel_xlen(&self) -> usize {
+        self.panel.get_xlen()
+    }
+
+    // Hidden window is not drawn, e.g. while another window is zoomed.
+    pub(crate) fn set_hidden(&mut self, t: bool) {
+        self.frame.set_hidden(t);
+        self.panel.set_hidden(t);
+    }
+
+    // Exchange position on screen with other window.
+    pub(crate) fn swap_screen(&mut self, other: &mut Self) -> Result<()> {
+        std::mem::swap(&mut self.frame, &mut other.frame);
+        std::mem::swap(&mut self.panel, &mut other.panel);
+        std::mem::swap(&mut self.painted, &mut other.painted);
+        for w in [self, other] {
+            if w.follow {
+                w.goto_tail()?;
+            }
+            w.update_title()?;
+        }
         Ok(())
     }
 
@@ -76,15 +422,186 @@ impl Window {
         self.panel.set_focus(t, standout_attr)
     }
 
-    pub(crate) fn goto_head(&mut self) {
+    fn update_title(&mut self) -> Result<()> {
+        let mut s = if self.bufv.len() > 1 {
+            format!("[{}/{}] ", self.bi + 1, self.bufv.len())
+        } else {
+            String::new()
+        };
+        s.push_str(self.buffer.get_path());
+        match self.buffer.get_state() {
+            buffer::State::Normal => (),
+            buffer::State::Truncated => s.push_str(" [truncated]"),
+            buffer::State::Rotated => s.push_str(" [rotated]"),
+            buffer::State::Missing => s.push_str(" [missing]"),
+            buffer::State::Exited => s.push_str(" [exited]"),
+            buffer::State::Waiting => s.push_str(" [waiting]"),
+        }
+        if let Some(x) = self.buffer.get_status() {
+            s.push_str(&format!(" [{x}]"));
+        }
+        if !self.filter.is_empty() {
+            s.push_str(" [filter]");
+        }
+        match self.delta.get_mode() {
+            delta::Mode::Raw => (),
+            delta::Mode::Delta => s.push_str(" [delta]"),
+            delta::Mode::Rate => s.push_str(" [rate]"),
+        }
+        if self.table.is_enabled() {
+            s.push_str(" [table]");
+        }
+        if self.follow {
+            s.push_str(" [follow]");
+        }
+        self.frame.set_title(&s)?;
+        self.panel.set_title(&s)
+    }
+
+    pub(crate) fn set_follow(&mut self, t: bool) -> Result<()> {
+        if self.follow == t {
+            return Ok(());
+        }
+        self.follow = t;
+        if self.follow {
+            self.goto_tail()?;
+        }
+        if self.is_dead() {
+            return Ok(());
+        }
+        self.update_title()
+    }
+
+    // Paths are absolute so that session is independent of current directory.
+    pub(crate) fn get_session(&self) -> Result<session::WindowSession> {
+        let mut paths = vec![];
+        for f in self.get_paths() {
+            paths.push(util::get_abspath(&f)?);
+        }
+        Ok(session::WindowSession {
+            paths,
+            current: self.bi,
+            offset: self.offset,
+            follow: self.follow,
+            include: self.filter.include.as_ref().map(|x| x.as_str().to_string()),
+            exclude: self.filter.exclude.as_ref().map(|x| x.as_str().to_string()),
+        })
+    }
+
+    // Restore states of current buffer, after paths in session were attached.
+    pub(crate) fn set_session(&mut self, s: &session::WindowSession) -> Result<()> {
+        if let Some(f) = s.paths.get(s.current) {
+            self.goto_path(f)?;
+        }
+        if self.is_dead() {
+            return Ok(());
+        }
+        self.filter.include = s.include.as_deref().map(regex::Regex::new).transpose()?;
+        self.filter.exclude = s.exclude.as_deref().map(regex::Regex::new).transpose()?;
+        self.tail = Tail::default();
+        self.offset = s.offset; // checked once read by update_buffer()
+        self.follow = s.follow;
+        self.update_filter()
+    }
+
+    pub(crate) fn is_table(&self) -> bool {
+        self.table.is_enabled()
+    }
+
+    pub(crate) fn set_table(&mut self, t: bool) -> Result<()> {
+        self.table.set_enabled(t);
+        if self.is_dead() {
+            return Ok(());
+        }
+        self.update_title()
+    }
+
+    pub(crate) fn is_follow(&self) -> bool {
+        self.follow
+    }
+
+    pub(crate) fn goto_head(&mut self) -> Result<()> {
         self.offset = 0;
+        self.set_follow(false)
     }
 
-    pub(crate) fn goto_tail(&mut self) {
-        self.offset = self.buffer.get_max_line();
+    // Keep the last page of the buffer visible, where folded lines take
+    // rows as many as they are painted in. Lines are read from where the
+    // previous call stopped, unless the buffer changed other than appended.
+    pub(crate) fn goto_tail(&mut self) -> Result<()> {
+        let ylen = self.panel.get_ylen();
+        let max = self.buffer.get_max_line();
+        if self.filter.is_empty() && !self.foldline {
+            self.offset = max.saturating_sub(ylen);
+            return Ok(());
+        }
+        if !self.buffer.is_ready() {
+            return Ok(());
+        }
+        let key = (
+            self.buffer.get_epoch(),
+            ylen,
+            self.panel.get_xlen(),
+            self.foldline,
+            self.showlnum,
+        );
+        if self.tail.key != Some(key) {
+            self.tail = Tail {
+                key: Some(key),
+                ..Tail::default()
+            };
+        }
+        // the last line may have been read without newline
+        let mut start = self.tail.scanned.saturating_sub(1);
+        if self.filter.is_empty() {
+            start = start.max(max.saturating_sub(ylen)); // a row each at least
+        }
+        while self.tail.lines.back().is_some_and(|x| x.0 >= start) {
+            if let Some((_, n)) = self.tail.lines.pop_back() {
+                self.tail.rows -= n;
+            }
+        }
+        self.buffer.seek_line(start)?;
+        while let Ok((pos, s, _)) = self.buffer.readline(false) {
+            if !self.filter.is_match(&s) {
+                continue;
+            }
+            let n = self.get_rows(pos, &s);
+            self.tail.lines.push_back((pos, n));
+            self.tail.rows += n;
+            while self.tail.rows > ylen && self.tail.lines.len() > 1 {
+                if let Some((_, n)) = self.tail.lines.pop_front() {
+                    self.tail.rows -= n;
+                }
+            }
+        }
+        self.tail.scanned = max;
+        if let Some(&(pos, _)) = self.tail.lines.front() {
+            self.offset = pos;
+        }
+        Ok(())
+    }
+
+    // number of rows line s at pos is painted in
+    fn get_rows(&self, pos: usize, s: &str) -> usize {
+        if !self.foldline {
+            return 1;
+        }
+        let p = if self.showlnum {
+            format!("{} ", pos + 1)
+        } else {
+            String::new()
+        };
+        util::split_width(&(p + s), self.panel.get_xlen()).len()
     }
 
     pub(crate) fn goto_current(&mut self, d: isize) -> Result<()> {
+        if d < 0 {
+            self.set_follow(false)?;
+        }
+        if !self.filter.is_empty() {
+            return self.goto_filtered(d);
+        }
         self.offset = if d < 0 {
             if self.offset < d.unsigned_abs() {
                 0
@@ -99,46 +616,300 @@ impl Window {
         Ok(())
     }
 
-    pub(crate) fn repaint(
+    // move by d lines which pass the filter
+    fn goto_filtered(&mut self, d: isize) -> Result<()> {
+        if !self.buffer.is_ready() {
+            return Ok(());
+        }
+        if d < 0 {
+            let v = self.get_filtered_lines(0, self.offset, d.unsigned_abs())?;
+            if let Some(&n) = v.front() {
+                self.offset = n;
+            }
+        } else {
+            let mut d = d.unsigned_abs();
+            self.buffer.seek_line(self.offset + 1)?;
+            while d > 0 {
+                let Ok((pos, s, _)) = self.buffer.readline(false) else {
+                    break;
+                };
+                if self.filter.is_match(&s) {
+                    self.offset = pos;
+                    d -= 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // last n lines within [start, end) which pass the filter
+    fn get_filtered_lines(
         &mut self,
-        showlnum: bool,
-        foldline: bool,
-        blinkline: bool,
-        standout_attr: u32,
-    ) -> Result<()> {
+        start: usize,
+        end: usize,
+        n: usize,
+    ) -> Result<std::collections::VecDeque<usize>> {
+        let mut v = std::collections::VecDeque::new();
+        if !self.buffer.is_ready() || n == 0 {
+            return Ok(v);
+        }
+        self.buffer.seek_line(start)?;
+        while let Ok((pos, s, _)) = self.buffer.readline(false) {
+            if pos >= end {
+                break;
+            }
+            if self.filter.is_match(&s) {
+                if v.len() == n {
+                    v.pop_front();
+                }
+                v.push_back(pos);
+            }
+        }
+        Ok(v)
+    }
+
+    // scroll horizontally, limited to the widest line of the last repaint
+    pub(crate) fn goto_column(&mut self, d: isize) {
+        let n = self.xmax.saturating_sub(self.panel.get_xlen());
+        self.xoffset = if d < 0 {
+            self.xoffset.saturating_sub(d.unsigned_abs())
+        } else {
+            (self.xoffset + d.unsigned_abs()).min(n)
+        };
+    }
+
+    pub(crate) fn goto_first_column(&mut self) {
+        self.xoffset = 0;
+    }
+
+    pub(crate) fn goto_last_column(&mut self) {
+        self.xoffset = self.xmax.saturating_sub(self.panel.get_xlen());
+    }
+
+    pub(crate) fn set_status(&mut self, s: &str) -> Result<()> {
+        self.frame.set_status(s)
+    }
+
+    // Set search pattern, or reuse current one if s is empty.
+    pub(crate) fn set_search(&mut self, s: &str, forward: bool) -> Result<()> {
+        if !s.is_empty() {
+            match regex::Regex::new(s) {
+                Ok(v) => self.search = Some(v),
+                Err(e) => {
+                    log::info!("{}: {}", util::function!(), e);
+                    return self.set_status(&format!("Invalid pattern: {s}"));
+                }
+            }
+        }
+        self.search_forward = forward;
+        self.goto_match(true)
+    }
+
+    // Move to next match in search direction, or opposite if !same.
+    pub(crate) fn goto_match(&mut self, same: bool) -> Result<()> {
+        let Some(re) = &self.search else {
+            return Ok(());
+        };
+        if !self.buffer.is_ready() {
+            return Ok(());
+        }
+        let forward = self.search_forward == same;
+        let (f, d, t) = (&self.filter, &self.delta, &self.table);
+        // pattern matches line as painted, as highlighted by repaint
+        if let Some(n) = self.buffer.find(self.offset, forward, |pos, s| {
+            f.is_match(s) && re.is_match(&t.format(&d.format(pos, s)))
+        })? {
+            self.offset = n;
+            self.set_follow(false)
+        } else {
+            let s = format!("Pattern not found: {}", re.as_str());
+            self.set_status(&s)
+        }
+    }
+
+    fn set_include(&mut self, re: Option<regex::Regex>) -> Result<()> {
+        self.filter.include = re;
+        self.update_filter()
+    }
+
+    fn set_exclude(&mut self, re: Option<regex::Regex>) -> Result<()> {
+        self.filter.exclude = re;
+        self.update_filter()
+    }
+
+    // Set include pattern, exclude pattern if s starts with "!",
+    // or clear both if s is empty.
+    pub(crate) fn set_filter(&mut self, s: &str) -> Result<()> {
+        if s.is_empty() {
+            self.filter = Filter::default();
+            return self.update_filter();
+        }
+        let (t, exclude) = match s.strip_prefix('!') {
+            Some(v) => (v, true),
+            None => (s, false),
+        };
+        let re = match regex::Regex::new(t) {
+            Ok(v) => v,
+            Err(e) => {
+                log::info!("{}: {}", util::function!(), e);
+                return self.set_status(&format!("Invalid pattern: {t}"));
+            }
+        };
+        if exclude {
+            self.set_exclude(Some(re))
+        } else {
+            self.set_include(Some(re))
+        }
+    }
+
+    fn update_filter(&mut self) -> Result<()> {
+        self.tail = Tail::default();
+        if self.follow {
+            self.goto_tail()?;
+        }
+        if self.is_dead() {
+            return Ok(());
+        }
+        self.update_title()
+    }
+
+    pub(crate) fn get_delta_mode(&self) -> delta::Mode {
+        self.delta.get_mode()
+    }
+
+    pub(crate) fn set_delta_mode(&mut self, mode: delta::Mode) -> Result<()> {
+        self.delta.set_mode(mode);
+        if self.is_dead() {
+            return Ok(());
+        }
+        self.update_title()
+    }
+
+    // column ranges of search matches in s, shifted by w columns
+    fn get_match_columns(&self, s: &str, w: usize) -> Vec<(usize, usize)> {
+        let Some(re) = &self.search else {
+            return vec![];
+        };
+        re.find_iter(s)
+            .filter(|m| !m.is_empty())
+            .map(|m| {
+                (
+                    w + util::get_width(&s[..m.start()]),
+                    w + util::get_width(&s[..m.end()]),
+                )
+            })
+            .collect()
+    }
+
+    // Only rows changed since the last repaint are printed, unless painted
+    // rows were dropped by resize, e.g. CTRL-l.
+    pub(crate) fn repaint(&mut self, standout_attr: u32, search_attr: u32) -> Result<()> {
         if self.is_dead() {
             return Ok(());
         }
+        if !self.buffer.is_ready() {
+            self.panel.erase()?;
+            self.painted.clear();
+            return self.panel.refresh();
+        }
 
-        let mut y = 0;
-        let offset = self.offset;
+        let ylen = self.panel.get_ylen();
         let xlen = self.panel.get_xlen();
+        let foldline = self.foldline;
 
-        self.panel.erase()?;
-        loop {
-            let Ok((pos, mut s, standout)) = self.buffer.readline(showlnum, blinkline) else {
+        // lines to paint, at most one per row, measured for table as rendered
+        // before any of them is, so that all rows are aligned
+        let mut l = vec![];
+        if self.delta.begin(self.buffer.get_generation()) {
+            // snapshot of every line, not only those painted, so that lines
+            // scrolled into view are compared with the same refresh
+            self.buffer.seek_line(0)?;
+            while let Ok((pos, s, _)) = self.buffer.readline(false) {
+                self.delta.record(pos, &s);
+            }
+        }
+        self.buffer.seek_line(self.offset)?;
+        while l.len() < ylen {
+            let Ok((pos, s, standout)) = self.buffer.readline(self.blinkline) else {
                 break;
             };
-            // C++ / Go version with fine grained lock checks ylen/xlen/offset mismatch here
-            if pos < offset {
+            if !self.filter.is_match(&s) {
                 continue;
             }
-            // XXX expecting s to only contain ascii
-            if !foldline && s.len() > xlen {
-                s = s.get(0..xlen).ok_or_else(|| xlen.to_string())?.to_string();
+            let s = self.delta.format(pos, &s);
+            if self.table.is_enabled() {
+                self.table.measure(&s);
             }
-            self.panel.print(y, 0, standout, standout_attr, &s)?;
+            l.push((pos, s, standout));
+        }
+
+        let mut v: Vec<Row> = vec![];
+        self.xmax = 0;
+        for (pos, s, standout) in l {
+            if v.len() >= ylen {
+                break;
+            }
+            let s = self.table.format(&s);
+            let p = if self.showlnum {
+                format!("{} ", pos + 1)
+            } else {
+                String::new()
+            };
+            let m = self.get_match_columns(&s, util::get_width(&p));
+            let s = p + &s;
+
+            // column ranges of s to print on each row
+            let mut rows = vec![];
             if !foldline {
-                y += 1;
+                self.xmax = self.xmax.max(util::get_width(&s));
+                rows.push((self.xoffset, xlen));
             } else {
-                y += s.len() / xlen;
-                if s.len() % xlen != 0 {
-                    y += 1;
+                let mut x = 0;
+                for t in util::split_width(&s, xlen) {
+                    let w = util::get_width(t);
+                    rows.push((x, w));
+                    x += w;
                 }
             }
+            for (x, w) in rows {
+                if v.len() >= ylen {
+                    break;
+                }
+                let mut row = vec![(0, standout, standout_attr, util::slice_width(&s, x, w))];
+                for &(ma, mb) in &m {
+                    let a = ma.max(x);
+                    let b = mb.min(x + w);
+                    if a < b {
+                        let t = util::slice_width(&s, a, b - a);
+                        row.push((a - x, true, search_attr, t));
+                    }
+                }
+                v.push(row);
+            }
+        }
+        v.resize(ylen, vec![]);
+
+        if self.painted.len() != ylen {
+            self.panel.erase()?;
+            self.painted = vec![vec![]; ylen];
+        }
+        for (y, row) in v.into_iter().enumerate() {
+            if self.painted[y] == row {
+                continue;
+            }
+            self.panel.erase_line(y)?;
+            for (x, standout, attr, t) in &row {
+                self.panel.print(y, *x, *standout, *attr, t)?;
+            }
+            self.painted[y] = row;
+        }
+        if foldline {
+            self.frame.set_clip(false, false)?;
+        } else {
+            self.frame
+                .set_clip(self.xoffset > 0, self.xmax > self.xoffset + xlen)?;
         }
         self.panel.refresh()?;
-        self.buffer.clear()?;
         Ok(())
     }
 }